@@ -0,0 +1,133 @@
+use crate::types::ArxivPaper;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+/// Render papers as a standalone Atom 1.0 feed.
+///
+/// `feed_id` should be stable for a given search so feed readers can track it;
+/// the arXiv query URL works well. The feed's `updated` is the newest entry
+/// update, falling back to the current time for an empty feed.
+pub fn to_atom(title: &str, feed_id: &str, papers: &[ArxivPaper]) -> String {
+    let updated = papers
+        .iter()
+        .map(|p| p.updated_date)
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\" xmlns:arxiv=\"http://arxiv.org/schemas/atom\">\n");
+    let _ = writeln!(xml, "  <id>{}</id>", escape(feed_id));
+    let _ = writeln!(xml, "  <title>{}</title>", escape(title));
+    let _ = writeln!(xml, "  <updated>{}</updated>", timestamp(updated));
+    let _ = writeln!(xml, "  <link rel=\"self\" href=\"{}\"/>", escape(feed_id));
+    xml.push_str("  <generator>noorle-arxiv</generator>\n");
+
+    for paper in papers {
+        xml.push_str("  <entry>\n");
+        let _ = writeln!(xml, "    <id>{}</id>", escape(&paper.url));
+        let _ = writeln!(xml, "    <title>{}</title>", escape(&paper.title));
+        let _ = writeln!(xml, "    <updated>{}</updated>", timestamp(paper.updated_date));
+        let _ = writeln!(xml, "    <published>{}</published>", timestamp(paper.published_date));
+        for author in &paper.authors {
+            let _ = writeln!(xml, "    <author><name>{}</name></author>", escape(author));
+        }
+        let _ = writeln!(xml, "    <summary>{}</summary>", escape(paper.abstract_text.trim()));
+        let _ = writeln!(
+            xml,
+            "    <link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>",
+            escape(&paper.url)
+        );
+        let _ = writeln!(
+            xml,
+            "    <link rel=\"related\" type=\"application/pdf\" title=\"pdf\" href=\"{}\"/>",
+            escape(&paper.pdf_url)
+        );
+        for category in &paper.categories {
+            let _ = writeln!(xml, "    <category term=\"{}\"/>", escape(category));
+        }
+        if let Some(primary_category) = &paper.primary_category {
+            let _ = writeln!(xml, "    <arxiv:primary_category term=\"{}\"/>", escape(primary_category));
+        }
+        if let Some(comment) = &paper.comment {
+            let _ = writeln!(xml, "    <arxiv:comment>{}</arxiv:comment>", escape(comment));
+        }
+        if let Some(doi) = &paper.doi {
+            let _ = writeln!(xml, "    <arxiv:doi>{}</arxiv:doi>", escape(doi));
+        }
+        if let Some(journal_ref) = &paper.journal_ref {
+            let _ = writeln!(xml, "    <arxiv:journal_ref>{}</arxiv:journal_ref>", escape(journal_ref));
+        }
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn timestamp(date: DateTime<Utc>) -> String {
+    date.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_feed;
+
+    const MULTIPLE_AUTHORS: &str = include_str!("../tests/fixtures/multiple_authors.xml");
+    const OLD_STYLE_ID: &str = include_str!("../tests/fixtures/old_style_id.xml");
+
+    #[test]
+    fn round_trips_through_the_feed_parser() {
+        let papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+        let xml = to_atom("arXiv search: gauge", "https://export.arxiv.org/api/query?search_query=gauge", &papers);
+
+        let reparsed = parse_feed(&xml).unwrap();
+        assert_eq!(reparsed.len(), papers.len());
+        for (original, copy) in papers.iter().zip(&reparsed) {
+            assert_eq!(copy.title, original.title);
+            assert_eq!(copy.authors, original.authors);
+            assert_eq!(copy.url, original.url);
+            assert_eq!(copy.pdf_url, original.pdf_url);
+            assert_eq!(copy.published_date, original.published_date);
+            assert_eq!(copy.updated_date, original.updated_date);
+            assert_eq!(copy.categories, original.categories);
+            assert_eq!(copy.primary_category, original.primary_category);
+            assert_eq!(copy.comment, original.comment);
+            assert_eq!(copy.doi, original.doi);
+            assert_eq!(copy.journal_ref, original.journal_ref);
+        }
+    }
+
+    #[test]
+    fn escapes_markup_in_text_and_attributes() {
+        let papers = parse_feed(OLD_STYLE_ID).unwrap();
+        let xml = to_atom("a < b & \"c\"", "urn:test?x=1&y=2", &papers);
+
+        assert!(xml.contains("<title>a &lt; b &amp; &quot;c&quot;</title>"));
+        assert!(xml.contains("href=\"urn:test?x=1&amp;y=2\""));
+        assert!(xml.contains("Quantum computation with {trapped} ions &amp; cavities"));
+    }
+
+    #[test]
+    fn newest_entry_sets_feed_updated() {
+        let papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+        let xml = to_atom("t", "urn:t", &papers);
+        assert!(xml.contains("  <updated>2025-09-19T17:58:55Z</updated>\n  <link"));
+    }
+}
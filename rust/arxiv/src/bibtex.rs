@@ -0,0 +1,169 @@
+use crate::types::ArxivPaper;
+use chrono::{DateTime, Datelike, Utc};
+use std::fmt::Write;
+
+/// Words skipped when picking the title word for a cite key.
+const KEY_STOPWORDS: &[&str] = &["a", "an", "the", "on", "of", "in", "for", "to", "and", "with", "towards", "toward"];
+
+/// Render a paper as a BibTeX `@article` entry citing its arXiv eprint.
+///
+/// The eprint is the unversioned id, matching arXiv's own BibTeX export, and
+/// the cite key follows the common `lastname2023firstword` convention.
+pub fn to_bibtex(paper: &ArxivPaper) -> String {
+    let eprint = &paper.paper_id;
+    let year = (paper.published_date != DateTime::<Utc>::MIN_UTC).then(|| paper.published_date.year());
+
+    let mut entry = String::new();
+    let _ = writeln!(entry, "@article{{{},", cite_key(paper, year));
+    let _ = writeln!(entry, "  title = {{{}}},", escape(&collapse_whitespace(&paper.title)));
+    if !paper.authors.is_empty() {
+        let authors: Vec<String> = paper.authors.iter().map(|a| escape(&collapse_whitespace(a))).collect();
+        let _ = writeln!(entry, "  author = {{{}}},", authors.join(" and "));
+    }
+    let _ = writeln!(entry, "  journal = {{arXiv preprint arXiv:{}}},", eprint);
+    if let Some(year) = year {
+        let _ = writeln!(entry, "  year = {{{}}},", year);
+    }
+    let _ = writeln!(entry, "  eprint = {{{}}},", eprint);
+    let _ = writeln!(entry, "  archivePrefix = {{arXiv}},");
+    if let Some(category) = paper.primary_category.as_ref().or(paper.categories.first()) {
+        let _ = writeln!(entry, "  primaryClass = {{{}}},", category);
+    }
+    let _ = writeln!(entry, "  url = {{https://arxiv.org/abs/{}}},", eprint);
+    entry.push('}');
+    entry
+}
+
+/// `lastname2023firstword` from the first author's last name, the year and the
+/// first title word that isn't a stopword. Letters are folded to lowercase ASCII.
+fn cite_key(paper: &ArxivPaper, year: Option<i32>) -> String {
+    let last_name = paper
+        .authors
+        .first()
+        .and_then(|author| author.split_whitespace().last())
+        .map(key_part)
+        .unwrap_or_default();
+    let title_word = paper
+        .title
+        .split_whitespace()
+        .map(key_part)
+        .find(|word| !word.is_empty() && !KEY_STOPWORDS.contains(&word.as_str()))
+        .unwrap_or_default();
+
+    let key = format!(
+        "{}{}{}",
+        last_name,
+        year.map(|y| y.to_string()).unwrap_or_default(),
+        title_word
+    );
+    if key.is_empty() {
+        "arxiv".to_string()
+    } else {
+        key
+    }
+}
+
+/// Lowercase ASCII letters and digits of `word`, with common accented Latin
+/// letters folded to their base letter ("Kebrič" -> "kebric").
+fn key_part(word: &str) -> String {
+    word.chars()
+        .flat_map(char::to_lowercase)
+        .filter_map(fold_ascii)
+        .filter(char::is_ascii_alphanumeric)
+        .collect()
+}
+
+fn fold_ascii(c: char) -> Option<char> {
+    if c.is_ascii() {
+        return Some(c);
+    }
+    let folded = match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'ł' | 'ĺ' | 'ļ' | 'ľ' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ß' => 's',
+        'ţ' | 'ť' => 't',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => return None,
+    };
+    Some(folded)
+}
+
+/// arXiv titles and names wrap across lines; BibTeX fields should not.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Escape characters that are special to BibTeX/LaTeX so field values compile
+/// as literal text. Non-ASCII letters are left as UTF-8.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '{' | '}' | '&' | '%' | '$' | '#' | '_' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_feed;
+
+    const MULTIPLE_AUTHORS: &str = include_str!("../tests/fixtures/multiple_authors.xml");
+    const OLD_STYLE_ID: &str = include_str!("../tests/fixtures/old_style_id.xml");
+
+    #[test]
+    fn renders_article_entry() {
+        let paper = parse_feed(MULTIPLE_AUTHORS).unwrap().remove(0);
+        let entry = to_bibtex(&paper);
+
+        assert!(entry.starts_with("@article{kebric2025exploring,\n"));
+        assert!(entry.contains("  title = {Exploring confinement transitions in Z2 lattice gauge theories},\n"));
+        assert!(entry.contains("  author = {Matjaž Kebrič and Lin Su and Alexander Douglas},\n"));
+        assert!(entry.contains("  year = {2025},\n"));
+        assert!(entry.contains("  eprint = {2509.16200},\n"));
+        assert!(entry.contains("  archivePrefix = {arXiv},\n"));
+        assert!(entry.contains("  primaryClass = {cond-mat.quant-gas},\n"));
+        assert!(entry.ends_with("  url = {https://arxiv.org/abs/2509.16200},\n}"));
+    }
+
+    #[test]
+    fn escapes_braces_and_special_characters() {
+        let paper = parse_feed(OLD_STYLE_ID).unwrap().remove(0);
+        let entry = to_bibtex(&paper);
+
+        assert!(entry.contains("title = {Quantum computation with \\{trapped\\} ions \\& cavities}"));
+        assert!(entry.contains("eprint = {quant-ph/0001087},"));
+        assert_eq!(escape("50% of $x_1$ ~ #2 ^ a\\b"), "50\\% of \\$x\\_1\\$ \\textasciitilde{} \\#2 \\textasciicircum{} a\\textbackslash{}b");
+    }
+
+    #[test]
+    fn cite_key_skips_stopwords_and_folds_accents() {
+        let mut paper = parse_feed(MULTIPLE_AUTHORS).unwrap().remove(0);
+        paper.title = "The {Attention} Is All You Need".to_string();
+        paper.authors = vec!["Łukasz Kaiser-Müller".to_string()];
+        assert_eq!(cite_key(&paper, Some(2017)), "kaisermuller2017attention");
+
+        paper.authors.clear();
+        paper.title = String::new();
+        assert_eq!(cite_key(&paper, None), "arxiv");
+    }
+}
@@ -0,0 +1,103 @@
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Directory holding cached search responses, one file per query URL.
+pub const CACHE_DIR: &str = "/tmp/arxiv-search-cache";
+
+/// How long a cached search response is served before arXiv is asked again.
+pub const TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Cache file for a query URL: the hex SHA-256 of the full URL, so every
+/// parameter (query, limit, sort order) is part of the key.
+fn entry_path(dir: &Path, url: &str) -> PathBuf {
+    let digest = Sha256::digest(url.as_bytes());
+    let key: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    dir.join(format!("{}.xml", key))
+}
+
+fn is_fresh(written: SystemTime, now: SystemTime, ttl: Duration) -> bool {
+    // A write time in the future (clock changes) is treated as stale.
+    now.duration_since(written).is_ok_and(|age| age < ttl)
+}
+
+/// The cached response body for `url`, if one was stored within `ttl`.
+pub fn lookup(dir: &Path, url: &str, ttl: Duration) -> Option<String> {
+    let path = entry_path(dir, url);
+    let written = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    if !is_fresh(written, SystemTime::now(), ttl) {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+/// Store a response body. Failures are ignored: the cache is only an optimization.
+pub fn store(dir: &Path, url: &str, body: &str) {
+    if std::fs::create_dir_all(dir).is_ok() {
+        let _ = std::fs::write(entry_path(dir, url), body);
+    }
+}
+
+/// Delete every cached response, returning how many were removed.
+pub fn clear(dir: &Path) -> std::io::Result<usize> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut removed = 0;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "xml") {
+            std::fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("arxiv-cache-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn keys_on_the_full_url() {
+        let dir = Path::new("/cache");
+        let a = entry_path(dir, "https://export.arxiv.org/api/query?search_query=gauge&max_results=10");
+        let b = entry_path(dir, "https://export.arxiv.org/api/query?search_query=gauge&max_results=20");
+        assert_ne!(a, b);
+        assert_eq!(a, entry_path(dir, "https://export.arxiv.org/api/query?search_query=gauge&max_results=10"));
+        assert_eq!(a.file_name().unwrap().len(), 64 + ".xml".len());
+    }
+
+    #[test]
+    fn expires_entries_after_ttl() {
+        let now = SystemTime::now();
+        assert!(is_fresh(now - Duration::from_secs(60), now, TTL));
+        assert!(!is_fresh(now - TTL, now, TTL));
+        assert!(!is_fresh(now + Duration::from_secs(60), now, TTL));
+    }
+
+    #[test]
+    fn stores_looks_up_and_clears() {
+        let dir = scratch_dir("roundtrip");
+        assert_eq!(lookup(&dir, "urn:a", TTL), None);
+        assert_eq!(clear(&dir).unwrap(), 0);
+
+        store(&dir, "urn:a", "<feed/>");
+        store(&dir, "urn:b", "<feed></feed>");
+        assert_eq!(lookup(&dir, "urn:a", TTL).as_deref(), Some("<feed/>"));
+        assert_eq!(lookup(&dir, "urn:a", Duration::ZERO), None);
+
+        assert_eq!(clear(&dir).unwrap(), 2);
+        assert_eq!(lookup(&dir, "urn:a", TTL), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
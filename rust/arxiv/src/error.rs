@@ -0,0 +1,139 @@
+use plugin_http::HttpError;
+use std::fmt;
+use std::string::FromUtf8Error;
+
+/// What went wrong, as reported across the WIT boundary. The error string is
+/// `"<kind>: <detail>"` with a stable kind prefix, so callers can decide
+/// whether to retry or surface the message without parsing the detail.
+#[derive(Debug, PartialEq)]
+pub enum PluginError {
+    /// arXiv could not be reached or a response could not be read.
+    Network(String),
+    /// arXiv answered with a non-2xx status not covered below.
+    HttpStatus(u16, String),
+    /// A feed or payload was not in the shape we expected.
+    Parse(String),
+    /// arXiv answered HTTP 429.
+    RateLimited(String),
+    /// An argument was rejected, by us or by arXiv's error feed.
+    InvalidInput(String),
+    /// The requested paper does not exist.
+    NotFound(String),
+    /// A file or directory under the save path could not be written.
+    Io(String),
+}
+
+impl PluginError {
+    /// The stable prefix of the WIT error string.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PluginError::Network(_) => "network",
+            PluginError::HttpStatus(..) => "http_status",
+            PluginError::Parse(_) => "parse",
+            PluginError::RateLimited(_) => "rate_limited",
+            PluginError::InvalidInput(_) => "invalid_input",
+            PluginError::NotFound(_) => "not_found",
+            PluginError::Io(_) => "io",
+        }
+    }
+
+    pub fn detail(&self) -> &str {
+        match self {
+            PluginError::Network(detail)
+            | PluginError::HttpStatus(_, detail)
+            | PluginError::Parse(detail)
+            | PluginError::RateLimited(detail)
+            | PluginError::InvalidInput(detail)
+            | PluginError::NotFound(detail)
+            | PluginError::Io(detail) => detail,
+        }
+    }
+
+    /// The same kind of error with `detail` in place of the original one.
+    pub fn with_detail(self, detail: String) -> Self {
+        match self {
+            PluginError::Network(_) => PluginError::Network(detail),
+            PluginError::HttpStatus(status, _) => PluginError::HttpStatus(status, detail),
+            PluginError::Parse(_) => PluginError::Parse(detail),
+            PluginError::RateLimited(_) => PluginError::RateLimited(detail),
+            PluginError::InvalidInput(_) => PluginError::InvalidInput(detail),
+            PluginError::NotFound(_) => PluginError::NotFound(detail),
+            PluginError::Io(_) => PluginError::Io(detail),
+        }
+    }
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.detail())
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// Classify an error from an internal function. Errors raised without a kind
+/// are argument checks, so they are reported as `invalid_input`.
+impl From<anyhow::Error> for PluginError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<PluginError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let detail = err.to_string();
+        for cause in err.chain() {
+            if let Some(HttpError::Transport { .. }) = cause.downcast_ref::<HttpError>() {
+                return PluginError::Network(detail);
+            }
+            if cause.is::<serde_json::Error>() || cause.is::<FromUtf8Error>() {
+                return PluginError::Parse(detail);
+            }
+            if cause.is::<std::io::Error>() {
+                return PluginError::Io(detail);
+            }
+        }
+        PluginError::InvalidInput(detail)
+    }
+}
+
+/// The WIT error string for an error from an internal function, with `what`
+/// failed leading the detail, e.g. `"network: Search failed: ..."`.
+/// Logged first when `NOORLE_DEBUG` is on.
+pub fn to_wit(err: anyhow::Error, what: &str) -> String {
+    let err = PluginError::from(err);
+    let detail = format!("{}: {}", what, err.detail());
+    plugin_http::debug_log!("error", kind = err.kind(), detail = detail);
+    format!("{}: {}", err.kind(), detail)
+}
+
+/// The WIT error string for `err`, logged first when `NOORLE_DEBUG` is on.
+pub fn report(err: PluginError) -> String {
+    plugin_http::debug_log!("error", kind = err.kind(), detail = err.detail());
+    err.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn prefixes_wit_errors_with_a_stable_kind() {
+        let transport = anyhow::Error::new(HttpError::Transport { message: "connection refused".to_string(), attempts: 1 })
+            .context("Failed to send request to arXiv API");
+        assert_eq!(to_wit(transport, "Search failed"), "network: Search failed: Failed to send request to arXiv API");
+
+        let not_found = anyhow::Error::from(PluginError::NotFound("Paper 2301.99999 not found on arXiv".to_string()));
+        assert_eq!(
+            to_wit(not_found, "Paper lookup failed"),
+            "not_found: Paper lookup failed: Paper 2301.99999 not found on arXiv"
+        );
+
+        let io = Err::<(), _>(std::io::Error::other("read-only file system"))
+            .context("Failed to create directory /papers")
+            .unwrap_err();
+        assert_eq!(to_wit(io, "Download failed"), "io: Download failed: Failed to create directory /papers");
+
+        let invalid = anyhow::anyhow!("Search query cannot be empty");
+        assert_eq!(to_wit(invalid, "Search failed"), "invalid_input: Search failed: Search query cannot be empty");
+    }
+}
@@ -1,59 +1,545 @@
 #![allow(unsafe_op_in_unsafe_fn)]
 
+mod atom;
+mod bibtex;
+mod cache;
+mod error;
+mod taxonomy;
+mod throttle;
 mod types;
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use std::time::Duration;
-use types::{ArxivPaper, DownloadResult};
-use waki::Client;
+use error::PluginError;
+use plugin_http::{HttpError, Response};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use types::{
+    ArchiveManifest, ArxivPaper, CacheClearResult, CategoryActivity, CategoryActivityReport, CategoryList,
+    DownloadResult, DownloadStatus, FeedCounts, ManifestEntry, SearchPage, SearchWithMetrics, VersionCheck,
+};
 
-wit_bindgen::generate!({
-    world: "arxiv-component",
-    path: "./wit",
-});
+mod bindings {
+    // The generated export shims take one argument per flattened WIT parameter.
+    #![allow(clippy::too_many_arguments)]
+
+    wit_bindgen::generate!({
+        world: "arxiv-component",
+        path: "./wit",
+    });
+}
+
+use bindings::*;
 
 const ARXIV_API_ENDPOINT: &str = "https://export.arxiv.org/api/query";
 const TIMEOUT_SECS: u64 = 30;
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; noorle-arxiv/1.0)";
+const MAX_ACTIVITY_CATEGORIES: usize = 20;
+const MAX_ACTIVITY_DAYS: u32 = 365;
+const MAX_BATCH_DOWNLOADS: usize = 50;
+const MANIFEST_FILE: &str = "manifest.json";
+/// Environment variable overriding the largest PDF a download will accept.
+const MAX_PDF_BYTES_VAR: &str = "ARXIV_MAX_PDF_BYTES";
+const DEFAULT_MAX_PDF_BYTES: u64 = 50 * 1024 * 1024;
+/// Searches list the newest submissions first unless a sort is given.
+const DEFAULT_SORT_BY: SortBy = SortBy::SubmittedDate;
+const DEFAULT_SORT_ORDER: SortOrder = SortOrder::Descending;
 
+/// A parsed search response.
+struct SearchFeed {
+    /// The `search_query` sent to arXiv, after field and category filters.
+    query: String,
+    papers: Vec<ArxivPaper>,
+    counts: FeedCounts,
+    /// How long the arXiv request took, or `None` when served from the cache.
+    elapsed_ms: Option<u32>,
+}
+
+/// Search arXiv, newest submissions first, serving identical searches from the
+/// `/tmp` cache for a few minutes.
 fn search_arxiv(query: String, max_results: u32) -> Result<Vec<ArxivPaper>> {
-    let max_results = max_results.min(100).max(1);
+    search_arxiv_feed(query, SearchField::All, Vec::new(), max_results, DEFAULT_SORT_BY, DEFAULT_SORT_ORDER)
+        .map(|feed| feed.papers)
+}
+
+/// Like `search_arxiv` with a field, category filters and an explicit sort,
+/// keeping the feed's result counts and timing.
+fn search_arxiv_feed(
+    query: String,
+    field: SearchField,
+    categories: Vec<String>,
+    max_results: u32,
+    sort_by: SortBy,
+    sort_order: SortOrder,
+) -> Result<SearchFeed> {
+    let query = search_query(&field_query(&query, field), &categories)?;
+    let url = search_url(&query, max_results, 0, sort_by, sort_order)?;
+    fetch_search_feed(&query, &url)
+}
+
+/// One page of results starting at the zero-based offset `start`, with the
+/// total match count so callers know when to stop paging.
+fn search_paged(query: String, max_results: u32, start: u32) -> Result<SearchPage> {
+    let url = search_url(&query, max_results, start, DEFAULT_SORT_BY, DEFAULT_SORT_ORDER)?;
+    let feed = fetch_search_feed(&query, &url)?;
+    Ok(SearchPage {
+        query_echo: feed.query,
+        result_count: feed.papers.len(),
+        papers: feed.papers,
+        start,
+        counts: feed.counts,
+    })
+}
+
+/// The newest submissions in one category, served from the `/tmp` cache like
+/// any other search.
+fn list_recent(category: String, max_results: u32) -> Result<Vec<ArxivPaper>> {
+    let (query, url) = recent_url(&category, max_results)?;
+    fetch_search_feed(&query, &url).map(|feed| feed.papers)
+}
+
+/// The `cat:` query and API URL for `list_recent`, sorted by submission date,
+/// newest first.
+fn recent_url(category: &str, max_results: u32) -> Result<(String, String)> {
+    let category = category.trim();
+    if category.is_empty() {
+        anyhow::bail!("Category cannot be empty");
+    }
+    let query = search_query("", &[category.to_string()])?;
+    let url = search_url(&query, max_results, 0, SortBy::SubmittedDate, SortOrder::Descending)?;
+    Ok((query, url))
+}
+
+/// Drop every abstract, or shorten each to at most `max_chars` characters.
+fn shape_abstracts(papers: &mut [ArxivPaper], max_chars: Option<u32>, include: bool) {
+    for paper in papers {
+        if !include {
+            paper.abstract_text.clear();
+        } else if let Some(max_chars) = max_chars {
+            paper.abstract_text = truncate_abstract(&paper.abstract_text, max_chars as usize);
+        }
+    }
+}
+
+/// Shorten `text` to at most `max_chars` characters plus a trailing "…",
+/// breaking at the last word boundary before the limit when there is one.
+/// Text already within the limit is returned trimmed but otherwise unchanged.
+fn truncate_abstract(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+        return text.to_string();
+    };
+
+    let head = &text[..cut];
+    // Cutting right before a space already ends on a whole word.
+    let head = if text[cut..].starts_with(char::is_whitespace) {
+        head
+    } else {
+        head.rfind(char::is_whitespace).map_or(head, |space| &head[..space])
+    };
+    format!("{}…", head.trim_end())
+}
+
+/// GET the search URL for `query` through the `/tmp` cache and parse the
+/// papers and result counts.
+fn fetch_search_feed(query: &str, url: &str) -> Result<SearchFeed> {
+    let cache_dir = Path::new(cache::CACHE_DIR);
+    let query = query.trim().to_string();
+
+    if let Some(body) = cache::lookup(cache_dir, url, cache::TTL) {
+        if let Ok(papers) = parse_feed(&body) {
+            return Ok(SearchFeed { query, papers, counts: feed_counts(&body), elapsed_ms: None });
+        }
+    }
+
+    let started = Instant::now();
+    let body = query_api(url)?;
+    let elapsed_ms = u32::try_from(started.elapsed().as_millis()).unwrap_or(u32::MAX);
+
+    let papers = parse_feed(&body)?;
+    cache::store(cache_dir, url, &body);
+    Ok(SearchFeed { query, papers, counts: feed_counts(&body), elapsed_ms: Some(elapsed_ms) })
+}
+
+fn clear_cache() -> Result<CacheClearResult> {
+    let entries_removed = cache::clear(Path::new(cache::CACHE_DIR))
+        .context("Failed to clear search cache")?;
+    Ok(CacheClearResult { entries_removed })
+}
+
+/// Run a search and re-serialize the results as an Atom feed for feed readers.
+fn search_as_atom(query: String, max_results: u32) -> Result<String> {
+    let feed_id = search_url(&query, max_results, 0, DEFAULT_SORT_BY, DEFAULT_SORT_ORDER)?;
+    let papers = search_arxiv(query.clone(), max_results)?;
+    Ok(atom::to_atom(&format!("arXiv search: {}", query.trim()), &feed_id, &papers))
+}
+
+/// Build the API URL for a free-text search, validating the query. `start` is
+/// the zero-based result offset; it is left out of the URL when 0 so first
+/// pages share cache entries with plain searches.
+fn search_url(query: &str, max_results: u32, start: u32, sort_by: SortBy, sort_order: SortOrder) -> Result<String> {
+    let query = query.trim();
+    if query.is_empty() {
+        anyhow::bail!("Search query cannot be empty");
+    }
+
+    let max_results = max_results.clamp(1, 100);
+
+    let encoded_query = urlencoding::encode(query);
+
+    let start = if start > 0 { format!("&start={}", start) } else { String::new() };
+
+    Ok(format!(
+        "{}?search_query={}{}&max_results={}&sortBy={}&sortOrder={}",
+        ARXIV_API_ENDPOINT,
+        encoded_query,
+        start,
+        max_results,
+        sort_by_param(sort_by),
+        sort_order_param(sort_order)
+    ))
+}
+
+/// Restrict free text to one metadata field with arXiv's field prefix, e.g.
+/// `au:"Yann LeCun"`. Multi-word text is quoted as a phrase. `All` leaves the
+/// text as-is, which arXiv already searches across every field.
+fn field_query(text: &str, field: SearchField) -> String {
+    let prefix = match field {
+        SearchField::All => return text.trim().to_string(),
+        SearchField::Title => "ti",
+        SearchField::Author => "au",
+        SearchField::Abstract => "abs",
+        SearchField::Category => "cat",
+    };
+
+    let text = text.trim().replace('"', "");
+    if text.is_empty() {
+        String::new()
+    } else if text.contains(char::is_whitespace) {
+        format!("{}:\"{}\"", prefix, text)
+    } else {
+        format!("{}:{}", prefix, text)
+    }
+}
+
+/// Combine free text with category filters into one `search_query`:
+/// `(<text>) AND (cat:cs.LG OR cat:math.AP)`. Either part may be empty, but not
+/// both. Categories must have the `archive.subject` shape.
+fn search_query(text: &str, categories: &[String]) -> Result<String> {
+    let text = text.trim();
+    let categories: Vec<&str> = categories
+        .iter()
+        .map(|c| c.trim())
+        .filter(|c| !c.is_empty())
+        .collect();
+
+    if let Some(invalid) = categories.iter().find(|c| !is_valid_category(c)) {
+        anyhow::bail!("Invalid arXiv category '{}': expected a form like 'cs.LG' or 'quant-ph'", invalid);
+    }
+    if categories.is_empty() {
+        return Ok(text.to_string());
+    }
+
+    let filter = categories
+        .iter()
+        .map(|c| format!("cat:{}", c))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    if text.is_empty() {
+        Ok(filter)
+    } else {
+        Ok(format!("({}) AND ({})", text, filter))
+    }
+}
+
+/// arXiv's `sortBy` value for a sort field.
+fn sort_by_param(sort_by: SortBy) -> &'static str {
+    match sort_by {
+        SortBy::Relevance => "relevance",
+        SortBy::SubmittedDate => "submittedDate",
+        SortBy::LastUpdatedDate => "lastUpdatedDate",
+    }
+}
+
+/// arXiv's `sortOrder` value for a direction.
+fn sort_order_param(sort_order: SortOrder) -> &'static str {
+    match sort_order {
+        SortOrder::Ascending => "ascending",
+        SortOrder::Descending => "descending",
+    }
+}
+
+/// Issue a GET against the arXiv API and return the raw Atom body.
+fn query_api(url: &str) -> Result<String> {
+    throttle::wait();
+    let headers = [("User-Agent", USER_AGENT)];
+    let body_bytes = match plugin_http::get_with_retry(url, &headers, Duration::from_secs(TIMEOUT_SECS), 0) {
+        Ok(body_bytes) => body_bytes,
+        Err(HttpError::Status { status, body, .. }) => {
+            // Malformed queries come back as a 400 with an error feed explaining why.
+            let detail = match api_error(&String::from_utf8_lossy(&body)) {
+                Some(message) => format!("arXiv API error: {} (status code {})", message, status),
+                None => format!("arXiv API returned status code: {}", status),
+            };
+            return Err(match status {
+                429 => PluginError::RateLimited(detail),
+                status => PluginError::HttpStatus(status, detail),
+            }
+            .into());
+        }
+        Err(e) => return Err(anyhow::Error::new(e).context("Failed to send request to arXiv API")),
+    };
+
+    String::from_utf8(body_bytes)
+        .context("Invalid UTF-8 in response")
+}
 
-    let encoded_query = urlencoding::encode(&query);
+/// Rank categories by how many papers were submitted to them in the last `since_days` days.
+///
+/// Each category costs one minimal search (`max_results=1`) whose
+/// `opensearch:totalResults` gives the submission count. Requests are spaced
+/// by the `throttle` interval to stay within arXiv's rate-limit guidance.
+fn category_activity(categories: Vec<String>, since_days: u32) -> Result<CategoryActivityReport> {
+    let categories: Vec<String> = categories
+        .iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect();
 
-    let url = format!(
-        "{}?search_query={}&max_results={}&sortBy=submittedDate&sortOrder=descending",
-        ARXIV_API_ENDPOINT, encoded_query, max_results
-    );
+    if categories.is_empty() {
+        anyhow::bail!("At least one category is required");
+    }
+    if categories.len() > MAX_ACTIVITY_CATEGORIES {
+        anyhow::bail!("At most {} categories can be compared at once", MAX_ACTIVITY_CATEGORIES);
+    }
+    if let Some(invalid) = categories.iter().find(|c| !is_valid_category(c)) {
+        anyhow::bail!("Invalid arXiv category '{}': expected a form like 'cs.LG' or 'quant-ph'", invalid);
+    }
 
-    let response = Client::new()
-        .get(&url)
-        .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-        .header("User-Agent", "Mozilla/5.0 (compatible; noorle-arxiv/1.0)")
-        .send()
-        .context("Failed to send request to arXiv API")?;
+    let since_days = since_days.clamp(1, MAX_ACTIVITY_DAYS);
+    let until = Utc::now();
+    let since = until - chrono::Duration::days(since_days as i64);
 
-    let status = response.status_code();
-    if !(200..300).contains(&status) {
-        anyhow::bail!("arXiv API returned status code: {}", status);
+    let mut activity = Vec::with_capacity(categories.len());
+    for category in categories {
+        let search_query = format!(
+            "cat:{} AND submittedDate:[{} TO {}]",
+            category,
+            since.format("%Y%m%d%H%M"),
+            until.format("%Y%m%d%H%M")
+        );
+        let url = format!(
+            "{}?search_query={}&max_results=1",
+            ARXIV_API_ENDPOINT,
+            urlencoding::encode(&search_query)
+        );
+
+        let body = query_api(&url)?;
+        let submissions = opensearch_value(&body, "totalResults")
+            .ok_or_else(|| PluginError::Parse(format!("arXiv response for '{}' had no totalResults", category)))?;
+
+        activity.push(CategoryActivity { category, submissions });
     }
 
-    let body_bytes = response.body()
-        .context("Failed to read response body")?;
+    activity.sort_by_key(|a| std::cmp::Reverse(a.submissions));
+
+    Ok(CategoryActivityReport {
+        since,
+        until,
+        categories: activity,
+    })
+}
+
+/// Compare a versioned paper id (e.g. "2301.08727v1") against the latest
+/// version arXiv has. Always queries arXiv directly rather than the search cache.
+fn check_latest_version(paper_id_with_version: String) -> Result<VersionCheck> {
+    let requested = paper_id_with_version.trim();
+    let (paper_id, _) = split_version(requested).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Paper id '{}' has no version; expected e.g. 2301.08727v1",
+            requested
+        )
+    })?;
 
-    let body = String::from_utf8(body_bytes)
-        .context("Invalid UTF-8 in response")?;
+    let papers = parse_feed(&query_api(&id_list_url(paper_id))?)?;
 
+    version_check(requested, &papers)
+}
+
+/// Fetch one paper's latest metadata by id. Accepts bare ids, versioned ids,
+/// `arXiv:` prefixes and abs/pdf URLs. Always queries arXiv directly rather
+/// than the search cache.
+fn get_paper(paper_id: String) -> Result<ArxivPaper> {
+    let paper_id = normalize_paper_id(&paper_id)
+        .ok_or_else(|| anyhow::anyhow!("Paper id cannot be empty"))?;
+    let papers = parse_feed(&query_api(&id_list_url(&paper_id))?)?;
+
+    find_paper(papers, &paper_id)
+}
+
+/// Fetch a paper by id and render it as a BibTeX `@article` entry.
+fn to_bibtex(paper_id: String) -> Result<String> {
+    get_paper(paper_id).map(|paper| bibtex::to_bibtex(&paper))
+}
+
+/// The entry for `paper_id` (unversioned) in an `id_list` feed. arXiv answers
+/// unknown ids with a placeholder entry, so match on the id rather than taking
+/// the first entry.
+fn find_paper(papers: Vec<ArxivPaper>, paper_id: &str) -> Result<ArxivPaper> {
+    papers
+        .into_iter()
+        .find(|p| p.paper_id == paper_id)
+        .ok_or_else(|| PluginError::NotFound(format!("Paper {} not found on arXiv", paper_id)).into())
+}
+
+/// Reduce user input such as "https://arxiv.org/abs/2301.08727v2",
+/// "arXiv:2301.08727" or "quant-ph/0001087v1" to the unversioned id.
+fn normalize_paper_id(input: &str) -> Option<String> {
+    let id = strip_id_decorations(input);
+    let id = split_version(id).map_or(id, |(base, _)| base);
+
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// Strip the URL, `arXiv:` prefix and `.pdf` suffix around a paper id,
+/// keeping any version suffix.
+fn strip_id_decorations(input: &str) -> &str {
+    let mut id = input.trim();
+    for prefix in ["https://", "http://", "www.", "export.", "arxiv.org/abs/", "arxiv.org/pdf/"] {
+        id = id.strip_prefix(prefix).unwrap_or(id);
+    }
+    if id.get(..6).is_some_and(|scheme| scheme.eq_ignore_ascii_case("arxiv:")) {
+        id = &id[6..];
+    }
+    id.trim_end_matches('/').trim_end_matches(".pdf")
+}
+
+/// API URL for one paper by id. Without a version suffix, `id_list` returns
+/// the latest version of the paper.
+fn id_list_url(paper_id: &str) -> String {
+    format!(
+        "{}?id_list={}&max_results=1",
+        ARXIV_API_ENDPOINT,
+        urlencoding::encode(paper_id)
+    )
+}
+
+fn version_check(requested: &str, papers: &[ArxivPaper]) -> Result<VersionCheck> {
+    let (paper_id, requested_version) = split_version(requested)
+        .ok_or_else(|| anyhow::anyhow!("Paper id '{}' has no version", requested))?;
+
+    let latest = papers
+        .iter()
+        .find(|p| p.paper_id == paper_id)
+        .ok_or_else(|| PluginError::NotFound(format!("Paper {} not found on arXiv", paper_id)))?;
+    let current_version = latest
+        .version
+        .ok_or_else(|| PluginError::Parse(format!("arXiv returned an unversioned id '{}'", latest.versioned_id)))?;
+
+    Ok(VersionCheck {
+        paper_id: paper_id.to_string(),
+        requested_version,
+        current_version,
+        is_outdated: current_version > requested_version,
+        updated_date: latest.updated_date,
+    })
+}
+
+/// Split "2301.08727v2" or "quant-ph/0001087v1" into the base id and version number.
+fn split_version(paper_id: &str) -> Option<(&str, u32)> {
+    let (base, version) = paper_id.rsplit_once('v')?;
+    if base.is_empty() || version.is_empty() || !version.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((base, version.parse().ok()?))
+}
+
+/// Known arXiv categories, preferring a refreshed copy over the bundled table.
+fn list_categories() -> CategoryList {
+    taxonomy::load_cache().unwrap_or_else(taxonomy::bundled)
+}
+
+/// Re-fetch the taxonomy from arxiv.org and cache it for `list_categories`.
+///
+/// On any failure the previous list (cached or bundled) is returned with the
+/// reason in `refresh_error`, so callers always get a usable taxonomy.
+fn refresh_categories() -> CategoryList {
+    let refreshed = query_api(taxonomy::TAXONOMY_URL).and_then(|page| {
+        let categories = taxonomy::parse_taxonomy_page(&page);
+        if categories.is_empty() {
+            anyhow::bail!("No categories found on the taxonomy page");
+        }
+        taxonomy::store_cache(categories)
+    });
+
+    refreshed.unwrap_or_else(|e| CategoryList {
+        refresh_error: Some(format!("{:#}", e)),
+        ..list_categories()
+    })
+}
+
+/// Whether `category` has arXiv's `archive` or `archive.subject` shape (e.g. `quant-ph`, `cs.LG`).
+fn is_valid_category(category: &str) -> bool {
+    let mut parts = category.splitn(2, '.');
+    let archive = parts.next().unwrap_or_default();
+    let subject = parts.next();
+
+    let archive_ok = !archive.is_empty()
+        && archive.chars().all(|c| c.is_ascii_lowercase() || c == '-');
+    let subject_ok = subject.is_none_or(|s| {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+    });
+
+    archive_ok && subject_ok
+}
+
+/// The `opensearch:*` result counts from a feed header.
+fn feed_counts(body: &str) -> FeedCounts {
+    FeedCounts {
+        total_results: opensearch_value(body, "totalResults"),
+        start_index: opensearch_value(body, "startIndex"),
+        items_per_page: opensearch_value(body, "itemsPerPage"),
+    }
+}
+
+/// Read a numeric `opensearch:*` element (e.g. `totalResults`) from the feed header.
+///
+/// feed-rs drops unknown namespaces, so this is a small text scan.
+fn opensearch_value(body: &str, name: &str) -> Option<u64> {
+    let open_tag = format!("<opensearch:{}", name);
+    let start = body.find(&open_tag)?;
+    let after_open = start + body[start..].find('>')? + 1;
+    let end = after_open + body[after_open..].find('<')?;
+    body[after_open..end].trim().parse().ok()
+}
+
+/// Parse an arXiv Atom feed into paper metadata.
+///
+/// Kept separate from the HTTP call so the mapping can be exercised
+/// against recorded feeds in tests.
+fn parse_feed(body: &str) -> Result<Vec<ArxivPaper>> {
+    if let Some(message) = api_error(body) {
+        anyhow::bail!("arXiv API error: {}", message);
+    }
     let feed = feed_rs::parser::parse(body.as_bytes())
-        .context("Failed to parse arXiv feed")?;
+        .map_err(|e| PluginError::Parse(format!("Failed to parse arXiv feed: {}", e)))?;
+
+    // feed-rs drops the `arxiv:` extension elements, so read them from the raw
+    // entries, which appear in the same order as the parsed ones.
+    let mut blocks = entry_blocks(body).into_iter();
 
     let mut papers = Vec::new();
     for entry in feed.entries {
-        let paper_id = entry.id
+        let block = blocks.next().unwrap_or_default();
+        let versioned_id = entry.id
             .split("/abs/")
             .last()
             .unwrap_or(&entry.id)
             .to_string();
+        let (paper_id, version) = match split_version(&versioned_id) {
+            Some((base, version)) => (base.to_string(), Some(version)),
+            None => (versioned_id.clone(), None),
+        };
 
         let authors = entry.authors
             .iter()
@@ -69,16 +555,18 @@ fn search_arxiv(query: String, max_results: u32) -> Result<Vec<ArxivPaper>> {
             .iter()
             .find(|l| l.rel == Some("alternate".to_string()))
             .map(|l| l.href.clone())
-            .unwrap_or_else(|| format!("https://arxiv.org/abs/{}", paper_id));
+            .unwrap_or_else(|| format!("https://arxiv.org/abs/{}", versioned_id));
 
         let pdf_url = entry.links
             .iter()
             .find(|l| l.media_type.as_deref() == Some("application/pdf"))
             .map(|l| l.href.clone())
-            .unwrap_or_else(|| format!("https://arxiv.org/pdf/{}.pdf", paper_id));
+            .unwrap_or_else(|| format!("https://arxiv.org/pdf/{}.pdf", versioned_id));
 
         papers.push(ArxivPaper {
             paper_id,
+            version,
+            versioned_id,
             title: entry.title.map(|t| t.content).unwrap_or_default(),
             authors,
             abstract_text: entry.summary.map(|s| s.content).unwrap_or_default(),
@@ -87,93 +575,967 @@ fn search_arxiv(query: String, max_results: u32) -> Result<Vec<ArxivPaper>> {
             published_date: entry.published.unwrap_or(DateTime::<Utc>::MIN_UTC),
             updated_date: entry.updated.unwrap_or(DateTime::<Utc>::MIN_UTC),
             categories,
+            primary_category: tag_attribute(block, "arxiv:primary_category", "term"),
+            comment: element_text(block, "arxiv:comment"),
+            doi: element_text(block, "arxiv:doi"),
+            journal_ref: element_text(block, "arxiv:journal_ref"),
         });
     }
 
     Ok(papers)
 }
 
-fn download_arxiv_pdf(paper_id: String, save_path: String) -> Result<DownloadResult> {
-    let clean_paper_id = if paper_id.contains('/') {
-        paper_id.split('/').last().unwrap_or(&paper_id)
-    } else {
-        &paper_id
+/// The raw XML of each `<entry>` in a feed, in document order.
+fn entry_blocks(body: &str) -> Vec<&str> {
+    body.split("<entry")
+        .skip(1)
+        .filter(|rest| rest.starts_with(|c: char| c == '>' || c.is_whitespace()))
+        .map(|rest| rest.split("</entry>").next().unwrap_or(rest))
+        .collect()
+}
+
+/// Text of the first `tag` element within one entry (e.g. `arxiv:doi`), with
+/// entities decoded and line wrapping collapsed, or `None` if absent or blank.
+fn element_text(entry: &str, tag: &str) -> Option<String> {
+    let (_, after_tag) = find_tag(entry, tag)?;
+    let text = &after_tag[after_tag.find('>')? + 1..];
+    non_blank(&text[..text.find('<')?])
+}
+
+/// Value of `attribute` on the first `tag` element within one entry, e.g. the
+/// `term` of `<arxiv:primary_category term="hep-lat"/>`.
+fn tag_attribute(entry: &str, tag: &str, attribute: &str) -> Option<String> {
+    let (attributes, _) = find_tag(entry, tag)?;
+    let pattern = format!("{}=\"", attribute);
+    let value = attributes
+        .match_indices(&pattern)
+        .find(|&(i, _)| i == 0 || attributes[..i].ends_with(char::is_whitespace))
+        .map(|(i, _)| &attributes[i + pattern.len()..])?;
+    non_blank(&value[..value.find('"')?])
+}
+
+/// The attribute text of the first `<{tag} ...>` and everything from its
+/// attributes on.
+fn find_tag<'a>(entry: &'a str, tag: &str) -> Option<(&'a str, &'a str)> {
+    let open_tag = format!("<{}", tag);
+    let rest = entry
+        .match_indices(&open_tag)
+        .map(|(i, _)| &entry[i + open_tag.len()..])
+        .find(|rest| rest.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()))?;
+    Some((&rest[..rest.find('>')?], rest))
+}
+
+/// arXiv reports a bad query as a feed with a single entry whose id is under
+/// `/api/errors` and whose summary holds the message.
+fn api_error(body: &str) -> Option<String> {
+    entry_blocks(body)
+        .into_iter()
+        .find(|entry| element_text(entry, "id").is_some_and(|id| id.contains("/api/errors")))
+        .and_then(|entry| element_text(entry, "summary"))
+}
+
+/// Decode entities and collapse line wrapping, treating blank text as absent.
+fn non_blank(raw: &str) -> Option<String> {
+    let text = taxonomy::unescape(raw);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Download a paper's PDF into `save_path`, named `filename` (sanitized) or
+/// `{paper_id}.pdf` when no filename is given. A versioned id such as
+/// "2301.08727v1" fetches that version; an unversioned one the latest.
+fn download_arxiv_pdf(paper_id: String, save_path: String, filename: Option<String>) -> Result<DownloadResult> {
+    fetch_pdf(&paper_id, &save_path, filename).map(download_result)
+}
+
+/// [`download_arxiv_pdf`] with the reported failures kept typed, as in
+/// [`fetch_to_file`].
+fn fetch_pdf(paper_id: &str, save_path: &str, filename: Option<String>) -> Result<std::result::Result<String, PluginError>> {
+    let clean_paper_id = clean_paper_id(paper_id);
+    let file_name = match filename {
+        Some(name) => sanitize_filename(&name)?,
+        None => format!("{}.pdf", clean_paper_id),
     };
 
     let pdf_url = format!("https://arxiv.org/pdf/{}", clean_paper_id);
+    fetch_to_file(&pdf_url, "application/pdf", "PDF", save_path, Some(max_pdf_bytes()), |_| file_name)
+}
 
-    let response = Client::new()
-        .get(&pdf_url)
-        .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-        .header("User-Agent", "Mozilla/5.0 (compatible; noorle-arxiv/1.0)")
-        .header("Accept", "application/pdf")
-        .send()
-        .context("Failed to download PDF from arXiv")?;
-
-    let status = response.status_code();
-    if !(200..300).contains(&status) {
-        return Ok(DownloadResult {
-            success: false,
-            file_path: None,
-            error: Some(format!("Failed to download PDF: HTTP status {}", status)),
-        });
-    }
+/// Largest PDF body to accept, from the environment or the default.
+fn max_pdf_bytes() -> u64 {
+    parse_max_pdf_bytes(std::env::var(MAX_PDF_BYTES_VAR).ok().as_deref())
+}
 
-    let pdf_data = response.body()
-        .context("Failed to read PDF data")?;
+/// Falls back to the default when unset, zero or not a number.
+fn parse_max_pdf_bytes(value: Option<&str>) -> u64 {
+    value
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|bytes| *bytes > 0)
+        .unwrap_or(DEFAULT_MAX_PDF_BYTES)
+}
 
-    if pdf_data.is_empty() {
-        return Ok(DownloadResult {
-            success: false,
-            file_path: None,
-            error: Some("Received empty PDF data from arXiv".to_string()),
-        });
+/// Download several PDFs into `save_path`, one result per id in the same order.
+/// Requests are spaced a few seconds apart, and a failed id is recorded in its
+/// result rather than aborting the rest.
+fn download_pdfs(paper_ids: Vec<String>, save_path: String) -> Result<Vec<DownloadResult>> {
+    if paper_ids.len() > MAX_BATCH_DOWNLOADS {
+        anyhow::bail!("At most {} papers can be downloaded at once", MAX_BATCH_DOWNLOADS);
     }
 
-    let save_dir = if save_path.is_empty() {
-        "/tmp".to_string()
+    let mut results = Vec::with_capacity(paper_ids.len());
+    for paper_id in paper_ids {
+        let result = download_arxiv_pdf(paper_id, save_path.clone(), None)
+            .unwrap_or_else(|e| DownloadResult {
+                success: false,
+                file_path: None,
+                error: Some(format!("{:#}", e)),
+            });
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Download a paper's e-print source into `save_path` as `{paper_id}` plus an
+/// extension matching what arXiv sent (usually `.tar.gz`).
+fn download_source(paper_id: String, save_path: String) -> Result<DownloadResult> {
+    let clean_paper_id = clean_paper_id(&paper_id);
+    let source_url = format!("https://arxiv.org/e-print/{}", clean_paper_id);
+
+    download_to_file(&source_url, "*/*", "source", &save_path, None, |response| {
+        let header = |name: &str| {
+            response
+                .header(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let extension = source_extension(&header("Content-Type"), &header("Content-Encoding"));
+        format!("{}{}", clean_paper_id, extension)
+    })
+}
+
+/// Reduce a download id to the form used in download URLs and file names:
+/// URL and `arXiv:` decorations are stripped like in `get-paper`, and an
+/// old-style archive prefix is dropped (`quant-ph/0001087` -> `0001087`). The
+/// version suffix is kept so a specific version can be requested.
+fn clean_paper_id(paper_id: &str) -> &str {
+    let id = strip_id_decorations(paper_id);
+    id.rsplit('/').next().unwrap_or(id)
+}
+
+/// File extension for an e-print response. arXiv serves multi-file sources as
+/// a gzipped tarball, single-file sources as a gzipped `.tex`, and PDF-only
+/// submissions as the PDF itself.
+fn source_extension(content_type: &str, content_encoding: &str) -> &'static str {
+    let content_type = content_type.to_ascii_lowercase();
+    let gzipped = content_type.contains("gzip") || content_encoding.to_ascii_lowercase().contains("gzip");
+
+    if content_type.starts_with("application/pdf") {
+        ".pdf"
+    } else if content_type.contains("tar") && !gzipped {
+        ".tar"
+    } else if content_type.starts_with("application/x-eprint") && !content_type.contains("tar") {
+        ".gz"
     } else {
-        save_path.trim_end_matches('/').to_string()
+        ".tar.gz"
+    }
+}
+
+/// GET `url` and write the body into `save_path`, naming the file from the
+/// response once its headers are known. `what` labels error messages. HTTP
+/// errors, bodies over `max_bytes`, empty bodies and write failures are
+/// reported in the result rather than as an `Err`.
+fn download_to_file(
+    url: &str,
+    accept: &str,
+    what: &str,
+    save_path: &str,
+    max_bytes: Option<u64>,
+    file_name: impl FnOnce(&Response) -> String,
+) -> Result<DownloadResult> {
+    fetch_to_file(url, accept, what, save_path, max_bytes, file_name).map(download_result)
+}
+
+/// [`download_to_file`] with the reported failures kept as a typed error: the
+/// outer `Err` is a request that could not be completed, the inner one a
+/// response that produced no file.
+fn fetch_to_file(
+    url: &str,
+    accept: &str,
+    what: &str,
+    save_path: &str,
+    max_bytes: Option<u64>,
+    file_name: impl FnOnce(&Response) -> String,
+) -> Result<std::result::Result<String, PluginError>> {
+    throttle::wait();
+    let headers = [("User-Agent", USER_AGENT), ("Accept", accept)];
+    let response = match plugin_http::send_with_retry(url, &headers, Duration::from_secs(TIMEOUT_SECS), 0) {
+        Ok(response) => response,
+        Err(HttpError::Status { status, .. }) => return Ok(Err(download_status_error(what, status))),
+        Err(e) => return Err(anyhow::Error::new(e).context(format!("Failed to download {} from arXiv", what))),
     };
 
-    let file_path = format!("{}/{}.pdf", save_dir, clean_paper_id);
+    let file_path = format!("{}/{}", save_dir(save_path), file_name(&response));
+    let data = match max_bytes {
+        Some(max_bytes) => match plugin_http::read_body_limited(response, max_bytes) {
+            Ok(data) => data,
+            Err(e @ HttpError::TooLarge { .. }) => {
+                return Ok(Err(PluginError::Network(format!(
+                    "{} is too large: {} (set {} to raise the limit)",
+                    what, e, MAX_PDF_BYTES_VAR
+                ))));
+            }
+            Err(e) => return Err(PluginError::Network(format!("Failed to read {} data: {}", what, e)).into()),
+        },
+        None => response.body()
+            .map_err(|e| PluginError::Network(format!("Failed to read {} data: {}", what, e)))?,
+    };
 
-    match std::fs::write(&file_path, &pdf_data) {
-        Ok(_) => Ok(DownloadResult {
+    if data.is_empty() {
+        return Ok(Err(PluginError::Parse(format!("Received empty {} data from arXiv", what))));
+    }
+
+    Ok(match std::fs::write(&file_path, &data) {
+        Ok(_) => Ok(file_path),
+        Err(e) => Err(PluginError::Io(format!("Failed to write {} to disk: {}", what, e))),
+    })
+}
+
+/// The failure for a download answered with a non-2xx `status`.
+fn download_status_error(what: &str, status: u16) -> PluginError {
+    let detail = format!("Failed to download {}: HTTP status {}", what, status);
+    match status {
+        404 => PluginError::NotFound(detail),
+        429 => PluginError::RateLimited(detail),
+        status => PluginError::HttpStatus(status, detail),
+    }
+}
+
+/// The `download-pdf` result for a download that reached arXiv.
+fn download_result(fetched: std::result::Result<String, PluginError>) -> DownloadResult {
+    match fetched {
+        Ok(file_path) => DownloadResult {
             success: true,
             file_path: Some(file_path),
             error: None,
-        }),
-        Err(e) => Ok(DownloadResult {
+        },
+        Err(e) => DownloadResult {
             success: false,
             file_path: None,
-            error: Some(format!("Failed to write PDF to disk: {}", e)),
-        }),
+            error: Some(e.detail().to_string()),
+        },
+    }
+}
+
+/// Make a caller-chosen filename safe to join onto the save directory: path
+/// separators and control characters are dropped and `.pdf` is appended if
+/// missing. Names containing `..` are rejected outright.
+fn sanitize_filename(filename: &str) -> Result<String> {
+    if filename.contains("..") {
+        anyhow::bail!("Invalid filename '{}': must not contain '..'", filename);
+    }
+
+    let cleaned: String = filename
+        .chars()
+        .filter(|c| !matches!(c, '/' | '\\') && !c.is_control())
+        .collect();
+    let cleaned = cleaned.trim();
+    let stem = cleaned
+        .get(cleaned.len().saturating_sub(4)..)
+        .filter(|ext| ext.eq_ignore_ascii_case(".pdf"))
+        .map_or(cleaned, |_| &cleaned[..cleaned.len() - 4]);
+    if stem.trim().is_empty() {
+        anyhow::bail!("Invalid filename '{}': no usable characters", filename);
+    }
+
+    Ok(format!("{}.pdf", stem))
+}
+
+fn save_dir(save_path: &str) -> String {
+    if save_path.is_empty() {
+        "/tmp".to_string()
+    } else {
+        save_path.trim_end_matches('/').to_string()
+    }
+}
+
+/// Search arXiv, download every matching PDF into `save_path`, and write a
+/// `manifest.json` alongside them describing what was fetched.
+///
+/// By default individual download failures are recorded in the manifest rather
+/// than aborting the run. With `fail_fast` the first failure stops the run: the
+/// remaining papers are marked skipped, the manifest is still written, and an
+/// error listing the PDFs already on disk is returned. Downloads are spaced out
+/// to respect arXiv's rate limits.
+fn archive_search(query: String, max_results: u32, save_path: String, fail_fast: bool) -> Result<String> {
+    let papers = search_arxiv(query.clone(), max_results)?;
+
+    let dir = save_dir(&save_path);
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory {}", dir))?;
+
+    let mut entries = Vec::with_capacity(papers.len());
+    let mut stopped = None;
+    let mut papers = papers.into_iter();
+    for paper in papers.by_ref() {
+        // Fetch exactly the version the search returned.
+        let fetched = fetch_pdf(&paper.versioned_id, &dir, None)
+            .map_err(download_failure)
+            .and_then(|fetched| fetched);
+        entries.push(manifest_entry(paper, &fetched));
+        if let (true, Err(failure)) = (fail_fast, fetched) {
+            stopped = Some(failure);
+            break;
+        }
+    }
+    entries.extend(papers.map(skipped_entry));
+
+    let manifest = build_manifest(query.trim(), &dir, fail_fast, entries);
+    let content = serde_json::to_string_pretty(&manifest)
+        .context("Failed to serialize manifest")?;
+
+    let manifest_path = format!("{}/{}", dir, MANIFEST_FILE);
+    std::fs::write(&manifest_path, &content)
+        .with_context(|| format!("Failed to write {}", manifest_path))?;
+
+    if let Some(error) = stopped.and_then(|failure| fail_fast_error(&manifest, &manifest_path, failure)) {
+        return Err(error.into());
+    }
+
+    Ok(content)
+}
+
+/// The error for a fail-fast run that stopped early, naming the failed paper and
+/// every PDF that was already written before it. It keeps the kind of the
+/// download `failure` that stopped the run, so a 404 or a disk error isn't
+/// reported as a network problem.
+fn fail_fast_error(manifest: &ArchiveManifest, manifest_path: &str, failure: PluginError) -> Option<PluginError> {
+    if !manifest.fail_fast {
+        return None;
+    }
+    let failed = manifest
+        .papers
+        .iter()
+        .find(|p| p.status == DownloadStatus::Failed)?;
+
+    let written: Vec<&str> = manifest
+        .papers
+        .iter()
+        .filter_map(|p| p.file_path.as_deref())
+        .collect();
+    let written = if written.is_empty() {
+        "no PDFs were written".to_string()
+    } else {
+        format!("{} PDF(s) already written: {}", written.len(), written.join(", "))
+    };
+
+    Some(failure.with_detail(format!(
+        "stopped at {} ({}); {}; {} skipped; manifest written to {}",
+        failed.paper_id,
+        failed.error.as_deref().unwrap_or("download failed"),
+        written,
+        manifest.skipped,
+        manifest_path
+    )))
+}
+
+/// Classify a download that could not be completed, keeping the whole cause
+/// chain in the detail for the manifest.
+fn download_failure(err: anyhow::Error) -> PluginError {
+    let detail = format!("{:#}", err);
+    PluginError::from(err).with_detail(detail)
+}
+
+fn manifest_entry(paper: ArxivPaper, fetched: &std::result::Result<String, PluginError>) -> ManifestEntry {
+    let (status, file_path, error) = match fetched {
+        Ok(file_path) => (DownloadStatus::Downloaded, Some(file_path.clone()), None),
+        Err(e) => (DownloadStatus::Failed, None, Some(e.detail().to_string())),
+    };
+
+    ManifestEntry {
+        paper_id: paper.paper_id,
+        version: paper.version,
+        title: paper.title,
+        file_path,
+        status,
+        error,
+    }
+}
+
+/// Entry for a paper a fail-fast run never attempted.
+fn skipped_entry(paper: ArxivPaper) -> ManifestEntry {
+    ManifestEntry {
+        paper_id: paper.paper_id,
+        version: paper.version,
+        title: paper.title,
+        file_path: None,
+        status: DownloadStatus::Skipped,
+        error: None,
+    }
+}
+
+fn build_manifest(query: &str, save_path: &str, fail_fast: bool, papers: Vec<ManifestEntry>) -> ArchiveManifest {
+    let count = |status| papers.iter().filter(|p| p.status == status).count();
+
+    ArchiveManifest {
+        query: query.to_string(),
+        created_at: Utc::now(),
+        save_path: save_path.to_string(),
+        fail_fast,
+        downloaded: count(DownloadStatus::Downloaded),
+        failed: count(DownloadStatus::Failed),
+        skipped: count(DownloadStatus::Skipped),
+        papers,
     }
 }
 
 struct ArxivComponent;
 
 impl Guest for ArxivComponent {
-    fn search(query: String, max_results: u32) -> Result<String, String> {
-        match search_arxiv(query, max_results) {
+    fn search(query: String, max_results: u32, options: SearchOptions) -> Result<String, String> {
+        let sort_by = options.sort_by.unwrap_or(DEFAULT_SORT_BY);
+        let sort_order = options.sort_order.unwrap_or(DEFAULT_SORT_ORDER);
+        let field = options.field.unwrap_or(SearchField::All);
+        let feed = search_arxiv_feed(query, field, options.categories, max_results, sort_by, sort_order).map(|mut feed| {
+            shape_abstracts(&mut feed.papers, options.abstract_max_chars, options.include_abstract);
+            feed
+        });
+        match feed {
+            Ok(SearchFeed { query, papers, counts, elapsed_ms }) if options.with_metrics => {
+                let result_count = papers.len();
+                serde_json::to_string(&SearchWithMetrics { query_echo: query, result_count, papers, counts, elapsed_ms })
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Ok(feed) => {
+                serde_json::to_string(&feed.papers)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Search failed"))
+        }
+    }
+
+    fn search_paged(query: String, max_results: u32, start: u32) -> Result<String, String> {
+        match search_paged(query, max_results, start) {
+            Ok(page) => {
+                serde_json::to_string(&page)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Search failed"))
+        }
+    }
+
+    fn search_as_atom(query: String, max_results: u32) -> Result<String, String> {
+        search_as_atom(query, max_results).map_err(|e| error::to_wit(e, "Search failed"))
+    }
+
+    fn list_recent(category: String, max_results: u32) -> Result<String, String> {
+        match list_recent(category, max_results) {
             Ok(papers) => {
                 serde_json::to_string(&papers)
-                    .map_err(|e| format!("Failed to serialize results: {}", e))
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Recent listing failed"))
+        }
+    }
+
+    fn category_activity(categories: Vec<String>, since_days: u32) -> Result<String, String> {
+        match category_activity(categories, since_days) {
+            Ok(report) => {
+                serde_json::to_string(&report)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Category activity failed"))
+        }
+    }
+
+    fn archive_search(query: String, max_results: u32, save_path: String, fail_fast: bool) -> Result<String, String> {
+        archive_search(query, max_results, save_path, fail_fast).map_err(|e| error::to_wit(e, "Archive failed"))
+    }
+
+    fn get_paper(paper_id: String) -> Result<String, String> {
+        match get_paper(paper_id) {
+            Ok(paper) => {
+                serde_json::to_string(&paper)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Paper lookup failed"))
+        }
+    }
+
+    fn to_bibtex(paper_id: String) -> Result<String, String> {
+        to_bibtex(paper_id).map_err(|e| error::to_wit(e, "BibTeX export failed"))
+    }
+
+    fn check_latest_version(paper_id_with_version: String) -> Result<String, String> {
+        match check_latest_version(paper_id_with_version) {
+            Ok(check) => {
+                serde_json::to_string(&check)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
             }
-            Err(e) => Err(format!("Search failed: {}", e))
+            Err(e) => Err(error::to_wit(e, "Version check failed"))
         }
     }
 
-    fn download_pdf(paper_id: String, save_path: String) -> Result<String, String> {
-        match download_arxiv_pdf(paper_id, save_path) {
+    fn list_categories() -> Result<String, String> {
+        serde_json::to_string(&list_categories())
+            .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize categories: {}", e))))
+    }
+
+    fn refresh_categories() -> Result<String, String> {
+        serde_json::to_string(&refresh_categories())
+            .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize categories: {}", e))))
+    }
+
+    fn clear_cache() -> Result<String, String> {
+        match clear_cache() {
+            Ok(result) => {
+                serde_json::to_string(&result)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Clear cache failed"))
+        }
+    }
+
+    fn download_pdf(paper_id: String, save_path: String, filename: Option<String>) -> Result<String, String> {
+        match download_arxiv_pdf(paper_id, save_path, filename) {
+            Ok(result) => {
+                serde_json::to_string(&result)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Download failed"))
+        }
+    }
+
+    fn download_pdfs(paper_ids: Vec<String>, save_path: String) -> Result<String, String> {
+        match download_pdfs(paper_ids, save_path) {
+            Ok(results) => {
+                serde_json::to_string(&results)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => Err(error::to_wit(e, "Batch download failed"))
+        }
+    }
+
+    fn download_source(paper_id: String, save_path: String) -> Result<String, String> {
+        match download_source(paper_id, save_path) {
             Ok(result) => {
                 serde_json::to_string(&result)
-                    .map_err(|e| format!("Failed to serialize result: {}", e))
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
             }
-            Err(e) => Err(format!("Download failed: {}", e))
+            Err(e) => Err(error::to_wit(e, "Source download failed"))
         }
     }
+
+    fn required_config() -> Vec<ConfigVar> {
+        // The arXiv API is public, so only optional tuning variables are read.
+        vec![
+            ConfigVar {
+                name: throttle::MIN_INTERVAL_VAR.to_string(),
+                required: false,
+                description: format!(
+                    "Minimum seconds between arXiv requests (default {}, 0 disables the wait)",
+                    throttle::DEFAULT_MIN_INTERVAL_SECS
+                ),
+            },
+            ConfigVar {
+                name: MAX_PDF_BYTES_VAR.to_string(),
+                required: false,
+                description: format!(
+                    "Largest PDF download accepted, in bytes (default {})",
+                    DEFAULT_MAX_PDF_BYTES
+                ),
+            },
+            ConfigVar {
+                name: plugin_http::debug::DEBUG_VAR.to_string(),
+                required: false,
+                description: "Set to 1 to log requests, responses, retries and errors to stderr".to_string(),
+            },
+        ]
+    }
 }
 
-export!(ArxivComponent);
\ No newline at end of file
+export!(ArxivComponent with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPLE_AUTHORS: &str = include_str!("../tests/fixtures/multiple_authors.xml");
+    const MISSING_PDF_LINK: &str = include_str!("../tests/fixtures/missing_pdf_link.xml");
+    const OLD_STYLE_ID: &str = include_str!("../tests/fixtures/old_style_id.xml");
+    const EMPTY: &str = include_str!("../tests/fixtures/empty.xml");
+    const API_ERROR: &str = include_str!("../tests/fixtures/api_error.xml");
+
+    #[test]
+    fn parses_entries_with_multiple_authors() {
+        let papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+        assert_eq!(papers.len(), 2);
+
+        let paper = &papers[0];
+        assert_eq!(paper.paper_id, "2509.16200");
+        assert_eq!(paper.version, Some(1));
+        assert_eq!(paper.versioned_id, "2509.16200v1");
+        assert_eq!(paper.title, "Exploring confinement transitions in Z2 lattice gauge theories");
+        assert_eq!(paper.authors, vec!["Matjaž Kebrič", "Lin Su", "Alexander Douglas"]);
+        assert!(paper.abstract_text.contains("Confinement of particles"));
+        assert_eq!(paper.url, "http://arxiv.org/abs/2509.16200v1");
+        assert_eq!(paper.pdf_url, "http://arxiv.org/pdf/2509.16200v1");
+        assert_eq!(paper.published_date.to_rfc3339(), "2025-09-19T17:58:55+00:00");
+        assert_eq!(paper.categories, vec!["cond-mat.quant-gas", "quant-ph"]);
+
+        assert_eq!(papers[1].paper_id, "2301.08727");
+        assert_eq!(papers[1].version, Some(2));
+        assert_eq!(papers[1].authors.len(), 2);
+        assert_eq!(papers[1].updated_date.to_rfc3339(), "2023-03-02T10:15:00+00:00");
+    }
+
+    #[test]
+    fn falls_back_to_derived_links_when_missing() {
+        let papers = parse_feed(MISSING_PDF_LINK).unwrap();
+        assert_eq!(papers.len(), 1);
+        assert_eq!(papers[0].versioned_id, "2404.00001v1");
+        assert_eq!(papers[0].url, "https://arxiv.org/abs/2404.00001v1");
+        assert_eq!(papers[0].pdf_url, "https://arxiv.org/pdf/2404.00001v1.pdf");
+    }
+
+    #[test]
+    fn keeps_archive_prefix_of_old_style_ids() {
+        let papers = parse_feed(OLD_STYLE_ID).unwrap();
+        assert_eq!(papers.len(), 1);
+        assert_eq!(papers[0].paper_id, "quant-ph/0001087");
+        assert_eq!(papers[0].versioned_id, "quant-ph/0001087v1");
+        assert_eq!(papers[0].version, Some(1));
+        assert_eq!(papers[0].title, "Quantum computation with {trapped} ions & cavities");
+        assert_eq!(papers[0].pdf_url, "http://arxiv.org/pdf/quant-ph/0001087v1");
+    }
+
+    #[test]
+    fn reads_doi_and_journal_ref_extensions() {
+        let papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+        assert_eq!(papers[0].doi, None);
+        assert_eq!(papers[0].journal_ref, None);
+        assert_eq!(papers[1].doi.as_deref(), Some("10.1103/PhysRevD.107.054501"));
+        assert_eq!(papers[1].journal_ref.as_deref(), Some("Phys. Rev. D 107, 054501 (2023)"));
+
+        let json = serde_json::to_string(&papers[0]).unwrap();
+        assert!(!json.contains("doi") && !json.contains("journal_ref"));
+
+        let entry = "<arxiv:doi_note>x</arxiv:doi_note><arxiv:journal_ref>J. Phys. A &amp;\n  B 1 (2001)</arxiv:journal_ref>";
+        assert_eq!(element_text(entry, "arxiv:doi"), None);
+        assert_eq!(element_text(entry, "arxiv:journal_ref").as_deref(), Some("J. Phys. A & B 1 (2001)"));
+    }
+
+    #[test]
+    fn reads_primary_category_and_comment() {
+        let papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+        assert_eq!(papers[0].primary_category.as_deref(), Some("cond-mat.quant-gas"));
+        assert_eq!(papers[0].comment.as_deref(), Some("12 pages, 5 figures"));
+        assert_eq!(papers[1].primary_category.as_deref(), Some("hep-lat"));
+        assert_eq!(papers[1].comment, None);
+
+        let entry = r#"<arxiv:primary_category scheme="x" subterm="a" term="cs.LG"/>"#;
+        assert_eq!(tag_attribute(entry, "arxiv:primary_category", "term").as_deref(), Some("cs.LG"));
+        assert_eq!(tag_attribute(entry, "arxiv:primary_category", "label"), None);
+    }
+
+    #[test]
+    fn returns_no_papers_for_empty_feed() {
+        let papers = parse_feed(EMPTY).unwrap();
+        assert!(papers.is_empty());
+    }
+
+    #[test]
+    fn truncates_abstracts_at_word_boundaries() {
+        let text = "  Confinement of particles into bound states\nis a phenomenon. ";
+        assert_eq!(truncate_abstract(text, 100), "Confinement of particles into bound states\nis a phenomenon.");
+        assert_eq!(truncate_abstract(text, 20), "Confinement of…");
+        assert_eq!(truncate_abstract(text, 24), "Confinement of particles…");
+        assert_eq!(truncate_abstract("Supercalifragilistic", 5), "Super…");
+        // Multi-byte characters are counted, never split.
+        assert_eq!(truncate_abstract("Kebrič–Su ŽŽŽ", 6), "Kebrič…");
+        assert_eq!(truncate_abstract("ŽŽŽ", 3), "ŽŽŽ");
+    }
+
+    #[test]
+    fn shapes_or_drops_abstracts() {
+        let mut papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+        shape_abstracts(&mut papers, Some(11), true);
+        assert_eq!(papers[0].abstract_text, "Confinement…");
+        shape_abstracts(&mut papers, None, true);
+        assert_eq!(papers[0].abstract_text, "Confinement…");
+
+        shape_abstracts(&mut papers, Some(11), false);
+        assert!(papers.iter().all(|p| p.abstract_text.is_empty()));
+        assert!(!serde_json::to_string(&papers[0]).unwrap().contains("abstract_text"));
+    }
+
+    #[test]
+    fn surfaces_arxiv_error_feeds() {
+        let err = parse_feed(API_ERROR).unwrap_err();
+        assert_eq!(err.to_string(), "arXiv API error: incorrect id format for 2301.0872");
+        assert_eq!(api_error(EMPTY), None);
+        assert_eq!(api_error(MULTIPLE_AUTHORS), None);
+    }
+
+    #[test]
+    fn search_url_trims_and_rejects_blank_queries() {
+        let url = search_url("  quantum computing ", 500, 0, DEFAULT_SORT_BY, DEFAULT_SORT_ORDER).unwrap();
+        assert!(url.contains("search_query=quantum%20computing&max_results=100&"));
+        assert!(url.ends_with("&sortBy=submittedDate&sortOrder=descending"));
+        assert!(search_url("   ", 10, 0, DEFAULT_SORT_BY, DEFAULT_SORT_ORDER).is_err());
+    }
+
+    #[test]
+    fn search_url_pages_with_start() {
+        let url = search_url("quantum", 100, 200, DEFAULT_SORT_BY, DEFAULT_SORT_ORDER).unwrap();
+        assert!(url.contains("search_query=quantum&start=200&max_results=100&"));
+        let first_page = search_url("quantum", 100, 0, DEFAULT_SORT_BY, DEFAULT_SORT_ORDER).unwrap();
+        assert!(!first_page.contains("start="));
+    }
+
+    #[test]
+    fn combines_text_and_category_filters() {
+        let categories = vec!["cs.LG".to_string(), " math.AP ".to_string(), "".to_string()];
+        assert_eq!(
+            search_query(" diffusion ", &categories).unwrap(),
+            "(diffusion) AND (cat:cs.LG OR cat:math.AP)"
+        );
+        assert_eq!(search_query("", &categories[..1]).unwrap(), "cat:cs.LG");
+        assert_eq!(search_query(" quantum ", &[]).unwrap(), "quantum");
+
+        let err = search_query("diffusion", &["foobar123".to_string()]).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid arXiv category 'foobar123'"));
+    }
+
+    #[test]
+    fn builds_recent_category_url() {
+        let (query, url) = recent_url(" cs.LG ", 500).unwrap();
+        assert_eq!(query, "cat:cs.LG");
+        assert_eq!(
+            url,
+            format!(
+                "{}?search_query=cat%3Acs.LG&max_results=100&sortBy=submittedDate&sortOrder=descending",
+                ARXIV_API_ENDPOINT
+            )
+        );
+        assert!(recent_url("quant-ph", 0).unwrap().1.contains("&max_results=1&"));
+
+        assert_eq!(recent_url("  ", 10).unwrap_err().to_string(), "Category cannot be empty");
+        let err = recent_url("machine learning", 10).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid arXiv category 'machine learning'"));
+    }
+
+    #[test]
+    fn prefixes_query_with_search_field() {
+        assert_eq!(field_query(" quantum computing ", SearchField::All), "quantum computing");
+        assert_eq!(field_query("Yann LeCun", SearchField::Author), "au:\"Yann LeCun\"");
+        assert_eq!(field_query("transformers", SearchField::Title), "ti:transformers");
+        assert_eq!(field_query("\"error correction\"", SearchField::Abstract), "abs:\"error correction\"");
+        assert_eq!(field_query("cs.LG", SearchField::Category), "cat:cs.LG");
+        assert_eq!(field_query("  ", SearchField::Title), "");
+
+        let query = search_query(&field_query("LeCun", SearchField::Author), &["cs.LG".to_string()]).unwrap();
+        assert_eq!(query, "(au:LeCun) AND (cat:cs.LG)");
+    }
+
+    #[test]
+    fn search_url_maps_sort_options() {
+        let url = search_url("quantum", 10, 0, SortBy::Relevance, SortOrder::Descending).unwrap();
+        assert!(url.ends_with("&sortBy=relevance&sortOrder=descending"));
+        let url = search_url("quantum", 10, 0, SortBy::LastUpdatedDate, SortOrder::Ascending).unwrap();
+        assert!(url.ends_with("&sortBy=lastUpdatedDate&sortOrder=ascending"));
+    }
+
+    #[test]
+    fn reads_opensearch_counts() {
+        assert_eq!(opensearch_value(MULTIPLE_AUTHORS, "totalResults"), Some(4213));
+        assert_eq!(opensearch_value(MULTIPLE_AUTHORS, "itemsPerPage"), Some(2));
+        assert_eq!(opensearch_value(EMPTY, "totalResults"), Some(0));
+        assert_eq!(opensearch_value("<feed></feed>", "totalResults"), None);
+    }
+
+    #[test]
+    fn sanitizes_download_filenames() {
+        assert_eq!(sanitize_filename("attention-is-all-you-need").unwrap(), "attention-is-all-you-need.pdf");
+        assert_eq!(sanitize_filename("Paper.PDF").unwrap(), "Paper.pdf");
+        assert_eq!(sanitize_filename("/etc/notes\\draft.pdf").unwrap(), "etcnotesdraft.pdf");
+        assert_eq!(sanitize_filename(" a b ").unwrap(), "a b.pdf");
+
+        for name in ["../secret.pdf", "a/../../b", "..", "", "/", ".pdf"] {
+            assert!(sanitize_filename(name).is_err(), "{:?} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn picks_source_extension_from_headers() {
+        assert_eq!(source_extension("application/x-eprint-tar", "x-gzip"), ".tar.gz");
+        assert_eq!(source_extension("application/gzip", ""), ".tar.gz");
+        assert_eq!(source_extension("application/x-tar", ""), ".tar");
+        assert_eq!(source_extension("application/x-eprint", "x-gzip"), ".gz");
+        assert_eq!(source_extension("application/pdf", ""), ".pdf");
+        assert_eq!(source_extension("", ""), ".tar.gz");
+        assert_eq!(clean_paper_id("quant-ph/0001087"), "0001087");
+        assert_eq!(clean_paper_id("2301.08727v2"), "2301.08727v2");
+        assert_eq!(clean_paper_id(" arXiv:2301.08727v1 "), "2301.08727v1");
+        assert_eq!(clean_paper_id("https://arxiv.org/abs/2301.08727v3"), "2301.08727v3");
+        assert_eq!(clean_paper_id("https://arxiv.org/pdf/2301.08727.pdf"), "2301.08727");
+    }
+
+    #[test]
+    fn parses_max_pdf_bytes_override() {
+        assert_eq!(parse_max_pdf_bytes(None), DEFAULT_MAX_PDF_BYTES);
+        assert_eq!(parse_max_pdf_bytes(Some(" 1048576 ")), 1_048_576);
+        assert_eq!(parse_max_pdf_bytes(Some("0")), DEFAULT_MAX_PDF_BYTES);
+        assert_eq!(parse_max_pdf_bytes(Some("big")), DEFAULT_MAX_PDF_BYTES);
+    }
+
+    #[test]
+    fn rejects_oversized_download_batches() {
+        let ids = vec!["2301.08727".to_string(); MAX_BATCH_DOWNLOADS + 1];
+        let err = download_pdfs(ids, String::new()).unwrap_err();
+        assert_eq!(err.to_string(), "At most 50 papers can be downloaded at once");
+        assert!(download_pdfs(Vec::new(), String::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn collects_feed_counts() {
+        let counts = feed_counts(MULTIPLE_AUTHORS);
+        assert_eq!(counts, FeedCounts { total_results: Some(4213), start_index: Some(0), items_per_page: Some(2) });
+        assert_eq!(feed_counts("<feed></feed>"), FeedCounts::default());
+    }
+
+    #[test]
+    fn validates_category_shape() {
+        for valid in ["cs.LG", "math.AP", "quant-ph", "hep-th", "cond-mat.quant-gas"] {
+            assert!(is_valid_category(valid), "{} should be valid", valid);
+        }
+        for invalid in ["foobar123", "", "cs.", ".LG", "CS.LG", "cs LG", "cs.LG.x"] {
+            assert!(!is_valid_category(invalid), "{} should be invalid", invalid);
+        }
+    }
+
+    #[test]
+    fn manifest_records_downloads_and_failures() {
+        let mut papers = parse_feed(MULTIPLE_AUTHORS).unwrap().into_iter();
+        let entries = vec![
+            manifest_entry(papers.next().unwrap(), &Ok("/tmp/a.pdf".to_string())),
+            manifest_entry(papers.next().unwrap(), &Err(PluginError::Network("connection reset".to_string()))),
+        ];
+
+        let manifest = build_manifest("gauge", "/tmp", false, entries);
+        assert_eq!((manifest.downloaded, manifest.failed, manifest.skipped), (1, 1, 0));
+        assert_eq!(manifest.papers[0].status, DownloadStatus::Downloaded);
+        assert_eq!(manifest.papers[0].file_path.as_deref(), Some("/tmp/a.pdf"));
+        assert_eq!(manifest.papers[1].status, DownloadStatus::Failed);
+        assert_eq!(manifest.papers[1].error.as_deref(), Some("connection reset"));
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert!(json.contains("\"status\":\"failed\""));
+        let failure = PluginError::Network("connection reset".to_string());
+        assert_eq!(fail_fast_error(&manifest, "/tmp/manifest.json", failure), None);
+    }
+
+    #[test]
+    fn fail_fast_reports_written_pdfs_and_skips() {
+        let mut papers = parse_feed(MULTIPLE_AUTHORS).unwrap().into_iter();
+        let first = papers.next().unwrap();
+        let second = papers.next().unwrap();
+        let failed_id = second.paper_id.clone();
+        let entries = vec![
+            manifest_entry(first, &Ok("/tmp/a.pdf".to_string())),
+            manifest_entry(second, &Err(PluginError::Network("connection reset".to_string()))),
+            skipped_entry(ArxivPaper {
+                paper_id: "2301.00001".to_string(),
+                versioned_id: "2301.00001v1".to_string(),
+                ..parse_feed(MULTIPLE_AUTHORS).unwrap().remove(0)
+            }),
+        ];
+
+        let manifest = build_manifest("gauge", "/tmp", true, entries);
+        assert_eq!((manifest.downloaded, manifest.failed, manifest.skipped), (1, 1, 1));
+        assert_eq!(manifest.papers[2].status, DownloadStatus::Skipped);
+
+        let failure = PluginError::Network("connection reset".to_string());
+        let error = fail_fast_error(&manifest, "/tmp/manifest.json", failure).unwrap().to_string();
+        assert!(error.starts_with(&format!("network: stopped at {} (connection reset)", failed_id)));
+        assert!(error.contains("1 PDF(s) already written: /tmp/a.pdf"));
+        assert!(error.contains("1 skipped; manifest written to /tmp/manifest.json"));
+    }
+
+    #[test]
+    fn fail_fast_keeps_the_kind_of_the_failed_download() {
+        let paper = parse_feed(MULTIPLE_AUTHORS).unwrap().remove(0);
+        let failed_id = paper.paper_id.clone();
+        let detail = "Failed to write PDF to disk: read-only file system";
+        let entries = vec![manifest_entry(paper, &Err(PluginError::Io(detail.to_string())))];
+        let manifest = build_manifest("gauge", "/tmp", true, entries);
+
+        let error = fail_fast_error(&manifest, "/tmp/manifest.json", PluginError::Io(detail.to_string())).unwrap();
+        assert_eq!(error.kind(), "io");
+        assert!(error.to_string().starts_with(&format!(
+            "io: stopped at {} (Failed to write PDF to disk: read-only file system); no PDFs were written",
+            failed_id
+        )));
+
+        assert_eq!(download_status_error("PDF", 404).to_string(), "not_found: Failed to download PDF: HTTP status 404");
+        assert_eq!(download_status_error("PDF", 429).kind(), "rate_limited");
+        assert_eq!(download_status_error("PDF", 503).kind(), "http_status");
+    }
+
+    #[test]
+    fn splits_version_suffixes() {
+        assert_eq!(split_version("2301.08727v2"), Some(("2301.08727", 2)));
+        assert_eq!(split_version("quant-ph/0001087v1"), Some(("quant-ph/0001087", 1)));
+        assert_eq!(split_version("2301.08727"), None);
+        assert_eq!(split_version("solv-int/9901001"), None);
+        assert_eq!(split_version("v3"), None);
+    }
+
+    #[test]
+    fn normalizes_paper_ids() {
+        for input in [
+            "2301.08727",
+            " 2301.08727v2 ",
+            "arXiv:2301.08727v1",
+            "https://arxiv.org/abs/2301.08727v2",
+            "http://export.arxiv.org/abs/2301.08727",
+            "https://arxiv.org/pdf/2301.08727v2.pdf",
+        ] {
+            assert_eq!(normalize_paper_id(input).as_deref(), Some("2301.08727"), "{}", input);
+        }
+        assert_eq!(
+            normalize_paper_id("https://arxiv.org/abs/quant-ph/0001087v1").as_deref(),
+            Some("quant-ph/0001087")
+        );
+        assert_eq!(normalize_paper_id("  "), None);
+    }
+
+    #[test]
+    fn finds_requested_paper_in_id_list_feed() {
+        let papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+        let paper = find_paper(papers, "2301.08727").unwrap();
+        assert_eq!(paper.versioned_id, "2301.08727v2");
+
+        let err = find_paper(parse_feed(EMPTY).unwrap(), "2101.00001").unwrap_err();
+        assert_eq!(err.to_string(), "not_found: Paper 2101.00001 not found on arXiv");
+    }
+
+    #[test]
+    fn flags_outdated_versions() {
+        let papers = parse_feed(MULTIPLE_AUTHORS).unwrap();
+
+        let check = version_check("2301.08727v1", &papers).unwrap();
+        assert_eq!(check.paper_id, "2301.08727");
+        assert_eq!((check.requested_version, check.current_version), (1, 2));
+        assert!(check.is_outdated);
+        assert_eq!(check.updated_date.to_rfc3339(), "2023-03-02T10:15:00+00:00");
+
+        assert!(!version_check("2301.08727v2", &papers).unwrap().is_outdated);
+        assert!(version_check("2101.00001v1", &papers).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_feed() {
+        assert!(parse_feed("<html><body>Service unavailable</body></html>").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// Environment variable overriding the minimum gap between arXiv requests.
+pub const MIN_INTERVAL_VAR: &str = "ARXIV_MIN_INTERVAL_SECS";
+/// arXiv asks API clients to leave about three seconds between requests.
+pub const DEFAULT_MIN_INTERVAL_SECS: u64 = 3;
+
+// A component instance runs single-threaded, so a thread-local lives exactly as
+// long as the instance and needs no locking. Each new instance starts with no
+// previous request, so its first call is never delayed.
+thread_local! {
+    static LAST_REQUEST: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Sleep until the minimum interval has passed since the previous arXiv
+/// request, then record this one. This trades latency on back-to-back calls
+/// for staying within arXiv's rate-limit guidance.
+pub fn wait() {
+    let delay = remaining(LAST_REQUEST.get(), min_interval(), Instant::now());
+    if !delay.is_zero() {
+        std::thread::sleep(delay);
+    }
+    LAST_REQUEST.set(Some(Instant::now()));
+}
+
+/// How much of `interval` is left since `last`, or zero if there was no
+/// previous request or it is already long enough ago.
+fn remaining(last: Option<Instant>, interval: Duration, now: Instant) -> Duration {
+    last.map_or(Duration::ZERO, |last| interval.saturating_sub(now.saturating_duration_since(last)))
+}
+
+/// Interval from the environment, falling back to the default when unset or
+/// invalid. `0` disables throttling.
+fn min_interval() -> Duration {
+    Duration::from_secs(parse_min_interval(std::env::var(MIN_INTERVAL_VAR).ok().as_deref()))
+}
+
+fn parse_min_interval(value: Option<&str>) -> u64 {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_MIN_INTERVAL_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_out_the_rest_of_the_interval() {
+        let interval = Duration::from_secs(3);
+        let now = Instant::now();
+
+        assert_eq!(remaining(None, interval, now), Duration::ZERO);
+        assert_eq!(remaining(Some(now), interval, now + Duration::from_secs(1)), Duration::from_secs(2));
+        assert_eq!(remaining(Some(now), interval, now + Duration::from_secs(5)), Duration::ZERO);
+        assert_eq!(remaining(Some(now), Duration::ZERO, now), Duration::ZERO);
+    }
+
+    #[test]
+    fn parses_interval_override() {
+        assert_eq!(parse_min_interval(None), DEFAULT_MIN_INTERVAL_SECS);
+        assert_eq!(parse_min_interval(Some(" 5 ")), 5);
+        assert_eq!(parse_min_interval(Some("0")), 0);
+        assert_eq!(parse_min_interval(Some("soon")), DEFAULT_MIN_INTERVAL_SECS);
+    }
+}
@@ -2,23 +2,175 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct ArxivPaper {
+    /// The id without its version suffix, e.g. "2301.08727".
     pub paper_id: String,
+    /// The version arXiv returned, e.g. 2 for "2301.08727v2".
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version: Option<u32>,
+    /// The id as arXiv returned it, version included.
+    pub versioned_id: String,
     pub title: String,
     pub authors: Vec<String>,
+    /// Omitted when empty, e.g. when a search was asked to leave abstracts out.
+    #[serde(skip_serializing_if = "String::is_empty", default)]
     pub abstract_text: String,
     pub url: String,
     pub pdf_url: String,
     pub published_date: DateTime<Utc>,
     pub updated_date: DateTime<Utc>,
     pub categories: Vec<String>,
+    /// The category arXiv files the paper under, from `arxiv:primary_category`.
+    /// Not necessarily the first of `categories`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub primary_category: Option<String>,
+    /// Author comment such as "12 pages, 5 figures", from `arxiv:comment`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub comment: Option<String>,
+    /// DOI of the published version, from `arxiv:doi`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub doi: Option<String>,
+    /// Citation of the published version, from `arxiv:journal_ref`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub journal_ref: Option<String>,
+}
+
+/// Result counts from a search feed's `opensearch:*` header; each is `None` if
+/// the feed omitted it.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct FeedCounts {
+    /// Total matches for the query across all pages.
+    pub total_results: Option<u64>,
+    /// Zero-based offset of the first returned paper.
+    pub start_index: Option<u64>,
+    /// Page size arXiv applied to the request.
+    pub items_per_page: Option<u64>,
+}
+
+/// `search` output when metrics are requested; `elapsed_ms` is `None` for cached results.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct SearchWithMetrics {
+    /// The `search_query` sent to arXiv, after field and category filters.
+    pub query_echo: String,
+    /// Number of papers returned; `0` means the search ran and matched nothing.
+    pub result_count: usize,
+    pub papers: Vec<ArxivPaper>,
+    #[serde(flatten)]
+    pub counts: FeedCounts,
+    pub elapsed_ms: Option<u32>,
+}
+
+/// One page of `search-paged` results.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct SearchPage {
+    /// The `search_query` sent to arXiv.
+    pub query_echo: String,
+    /// Number of papers in this page.
+    pub result_count: usize,
+    pub papers: Vec<ArxivPaper>,
+    /// Zero-based offset of the first paper in this page.
+    pub start: u32,
+    #[serde(flatten)]
+    pub counts: FeedCounts,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct DownloadResult {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct CategoryActivity {
+    pub category: String,
+    pub submissions: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct CategoryActivityReport {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub categories: Vec<CategoryActivity>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ManifestEntry {
+    pub paper_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    pub status: DownloadStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadStatus {
+    Downloaded,
+    Failed,
+    /// Not attempted because a fail-fast run had already stopped.
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ArchiveManifest {
+    pub query: String,
+    pub created_at: DateTime<Utc>,
+    pub save_path: String,
+    /// Whether the run stopped at the first failed download.
+    pub fail_fast: bool,
+    pub downloaded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub papers: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct Category {
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct CategoryList {
+    /// "cache" for a refreshed copy, "bundled" for the table compiled into the plugin.
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_error: Option<String>,
+    pub categories: Vec<Category>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct CacheClearResult {
+    pub entries_removed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct VersionCheck {
+    pub paper_id: String,
+    pub requested_version: u32,
+    pub current_version: u32,
+    pub is_outdated: bool,
+    pub updated_date: DateTime<Utc>,
+}
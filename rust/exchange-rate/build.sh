@@ -74,13 +74,19 @@ else
   echo "✅ wasm32-wasip2 target already installed"
 fi
 
+# Optional cargo features (e.g. FEATURES=camel-case)
+FEATURE_ARGS=()
+if [ -n "$FEATURES" ]; then
+    FEATURE_ARGS=(--features "$FEATURES")
+fi
+
 # Build the project
 echo "Building Rust project to WASM in $MODE mode..."
 if [ "$MODE" = "release" ]; then
-    cargo build --target wasm32-wasip2 --release
+    cargo build --target wasm32-wasip2 --release "${FEATURE_ARGS[@]}"
     WASM_DIR="target/wasm32-wasip2/release"
 else
-    cargo build --target wasm32-wasip2
+    cargo build --target wasm32-wasip2 "${FEATURE_ARGS[@]}"
     WASM_DIR="target/wasm32-wasip2/debug"
 fi
 
@@ -0,0 +1,165 @@
+use crate::error::PluginError;
+use anyhow::Result;
+
+/// Names and symbols people type instead of an ISO code, matched after
+/// trimming, lowercasing and collapsing whitespace.
+const ALIASES: &[(&str, &str)] = &[
+    // US dollar
+    ("dollar", "usd"),
+    ("dollars", "usd"),
+    ("us dollar", "usd"),
+    ("us dollars", "usd"),
+    ("u.s. dollar", "usd"),
+    ("american dollar", "usd"),
+    ("us$", "usd"),
+    ("usd$", "usd"),
+    // Other dollars
+    ("canadian dollar", "cad"),
+    ("c$", "cad"),
+    ("ca$", "cad"),
+    ("australian dollar", "aud"),
+    ("a$", "aud"),
+    ("au$", "aud"),
+    ("new zealand dollar", "nzd"),
+    ("nz$", "nzd"),
+    ("hong kong dollar", "hkd"),
+    ("hk$", "hkd"),
+    ("singapore dollar", "sgd"),
+    ("s$", "sgd"),
+    ("mexican peso", "mxn"),
+    ("mx$", "mxn"),
+    // Europe
+    ("euro", "eur"),
+    ("euros", "eur"),
+    ("€", "eur"),
+    ("pound", "gbp"),
+    ("pounds", "gbp"),
+    ("pound sterling", "gbp"),
+    ("sterling", "gbp"),
+    ("british pound", "gbp"),
+    ("£", "gbp"),
+    ("swiss franc", "chf"),
+    ("swedish krona", "sek"),
+    ("norwegian krone", "nok"),
+    ("danish krone", "dkk"),
+    ("zloty", "pln"),
+    ("polish zloty", "pln"),
+    ("zł", "pln"),
+    ("ruble", "rub"),
+    ("rouble", "rub"),
+    ("russian ruble", "rub"),
+    ("₽", "rub"),
+    ("lira", "try"),
+    ("turkish lira", "try"),
+    ("₺", "try"),
+    ("hryvnia", "uah"),
+    ("₴", "uah"),
+    // Asia
+    ("yen", "jpy"),
+    ("japanese yen", "jpy"),
+    ("yuan", "cny"),
+    ("chinese yuan", "cny"),
+    ("renminbi", "cny"),
+    ("rmb", "cny"),
+    ("indian rupee", "inr"),
+    ("₹", "inr"),
+    ("won", "krw"),
+    ("korean won", "krw"),
+    ("south korean won", "krw"),
+    ("₩", "krw"),
+    ("baht", "thb"),
+    ("฿", "thb"),
+    ("shekel", "ils"),
+    ("₪", "ils"),
+    ("dong", "vnd"),
+    ("₫", "vnd"),
+    ("₱", "php"),
+    // Elsewhere
+    ("real", "brl"),
+    ("brazilian real", "brl"),
+    ("r$", "brl"),
+    ("rand", "zar"),
+    ("south african rand", "zar"),
+    ("naira", "ngn"),
+    ("₦", "ngn"),
+    // Crypto
+    ("bitcoin", "btc"),
+    ("₿", "btc"),
+    ("ether", "eth"),
+    ("ethereum", "eth"),
+];
+
+/// Symbols and names shared by several currencies. These are rejected with
+/// the candidates rather than guessed.
+const AMBIGUOUS: &[(&str, &[&str])] = &[
+    ("$", &["usd", "cad", "aud", "nzd", "hkd", "sgd", "mxn"]),
+    ("¥", &["jpy", "cny"]),
+    ("kr", &["sek", "nok", "dkk", "isk"]),
+    ("franc", &["chf", "xof", "xaf"]),
+    ("krona", &["sek", "isk"]),
+    ("krone", &["nok", "dkk"]),
+    ("peso", &["mxn", "ars", "clp", "cop", "php"]),
+    ("rupee", &["inr", "pkr", "lkr", "npr"]),
+];
+
+/// The lowercase currency code for `input`: an alias from the table, or the
+/// input itself (trimmed and lowercased) when it isn't one. An ambiguous
+/// symbol or name is an error listing the codes it could mean.
+pub fn resolve(input: &str) -> Result<String> {
+    let key = input.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+
+    if let Some((_, code)) = ALIASES.iter().find(|(alias, _)| *alias == key) {
+        return Ok(code.to_string());
+    }
+    if let Some((_, candidates)) = AMBIGUOUS.iter().find(|(alias, _)| *alias == key) {
+        return Err(PluginError::InvalidInput(format!(
+            "Ambiguous currency '{}': could be {}; pass the ISO code instead",
+            input.trim(),
+            candidates.join(", ")
+        ))
+        .into());
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_names_and_symbols() {
+        assert_eq!(resolve("dollar").unwrap(), "usd");
+        assert_eq!(resolve("USD$").unwrap(), "usd");
+        assert_eq!(resolve("  US   Dollar ").unwrap(), "usd");
+        assert_eq!(resolve("€").unwrap(), "eur");
+        assert_eq!(resolve("Pound Sterling").unwrap(), "gbp");
+        assert_eq!(resolve("R$").unwrap(), "brl");
+    }
+
+    #[test]
+    fn passes_codes_through_lowercased() {
+        assert_eq!(resolve(" USD ").unwrap(), "usd");
+        assert_eq!(resolve("btc").unwrap(), "btc");
+        assert_eq!(resolve("xyz").unwrap(), "xyz");
+        assert_eq!(resolve("").unwrap(), "");
+    }
+
+    #[test]
+    fn rejects_ambiguous_symbols_with_candidates() {
+        let err = resolve(" $ ").unwrap_err();
+        assert_eq!(
+            PluginError::from(err).to_string(),
+            "invalid_input: Ambiguous currency '$': could be usd, cad, aud, nzd, hkd, sgd, mxn; pass the ISO code instead"
+        );
+        assert!(resolve("¥").unwrap_err().to_string().contains("jpy, cny"));
+    }
+
+    #[test]
+    fn no_alias_is_also_ambiguous() {
+        for (alias, code) in ALIASES {
+            assert!(!AMBIGUOUS.iter().any(|(ambiguous, _)| ambiguous == alias), "{} is listed twice", alias);
+            assert_eq!(*alias, alias.to_lowercase(), "{} must be lowercase", alias);
+            assert_eq!(code.len(), 3, "{} -> {}", alias, code);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::round_half_up;
+use anyhow::Result;
+
+/// Display symbol and decimal places for currencies with a well-known sign.
+/// Anything else is shown as `"CODE 1234.56"`.
+const SYMBOLS: &[(&str, &str, usize)] = &[
+    ("usd", "$", 2),
+    ("eur", "€", 2),
+    ("gbp", "£", 2),
+    ("jpy", "¥", 0),
+    ("cny", "CN¥", 2),
+    ("inr", "₹", 2),
+    ("krw", "₩", 0),
+    ("cad", "CA$", 2),
+    ("aud", "A$", 2),
+];
+
+/// Characters treated as thousands separators wherever they appear: spaces
+/// (including no-break and narrow no-break), apostrophes and underscores.
+const GROUP_SEPARATORS: &[char] = &[' ', '\u{a0}', '\u{202f}', '\'', '_'];
+
+/// Parse a human-formatted amount such as `"1,234.56"`, `"1.234,56"` or `"1 000,50"`.
+///
+/// When both `.` and `,` appear, the last one is the decimal separator. When only
+/// one of them appears, it is a thousands separator if it occurs more than once, or
+/// once with exactly three digits after it and a non-zero integer part (`"1,234"`).
+/// Otherwise it is the decimal separator (`"12,5"`, `"0,125"`). Anything with letters or
+/// other symbols is rejected.
+pub fn parse_amount(input: &str) -> Result<f64> {
+    let invalid = || anyhow::anyhow!("Invalid amount: {}", input);
+
+    let trimmed = input.trim();
+    let (sign, unsigned) = match trimmed.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+
+    let compact: String = unsigned.chars().filter(|c| !GROUP_SEPARATORS.contains(c)).collect();
+    if compact.is_empty() || !compact.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+        return Err(invalid());
+    }
+    if !compact.chars().any(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let decimal = decimal_separator(&compact);
+    let normalized: String = compact
+        .chars()
+        .filter(|&c| c.is_ascii_digit() || Some(c) == decimal)
+        .map(|c| if Some(c) == decimal { '.' } else { c })
+        .collect();
+    if normalized.matches('.').count() > 1 {
+        return Err(invalid());
+    }
+
+    let value: f64 = normalized.parse().map_err(|_| invalid())?;
+    if !value.is_finite() {
+        return Err(invalid());
+    }
+    Ok(sign * value)
+}
+
+/// Format `value` for display in `currency` (a lowercase code), e.g.
+/// `"€1,234.56"` or `"¥1,235"`: the currency's symbol, comma thousands
+/// separators and its usual decimal places, rounded half-up. Currencies without
+/// a symbol fall back to `"CHF 1234.56"`.
+pub fn format_amount(value: f64, currency: &str) -> String {
+    let Some((_, symbol, decimals)) = SYMBOLS.iter().find(|(code, _, _)| *code == currency) else {
+        return format!("{} {:.2}", currency.to_uppercase(), round_half_up(value, 2));
+    };
+
+    let rounded = round_half_up(value, *decimals);
+    let digits = format!("{:.*}", decimals, rounded.abs());
+    let (int_part, frac_part) = match digits.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, format!(".{}", frac_part)),
+        None => (digits.as_str(), String::new()),
+    };
+    let sign = if rounded < 0.0 { "-" } else { "" };
+    format!("{}{}{}{}", sign, symbol, group_thousands(int_part), frac_part)
+}
+
+/// Insert a comma between each group of three integer digits.
+fn group_thousands(int_part: &str) -> String {
+    let mut grouped = String::with_capacity(int_part.len() + int_part.len() / 3);
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Which of `.`/`,` (if any) marks the decimal point in a digits-and-separators string.
+fn decimal_separator(compact: &str) -> Option<char> {
+    let last_dot = compact.rfind('.');
+    let last_comma = compact.rfind(',');
+
+    let separator = match (last_dot, last_comma) {
+        (None, None) => return None,
+        (Some(dot), Some(comma)) => return Some(if dot > comma { '.' } else { ',' }),
+        (Some(_), None) => '.',
+        (None, Some(_)) => ',',
+    };
+
+    if compact.matches(separator).count() > 1 {
+        return None;
+    }
+    let (int_part, frac_part) = compact.split_once(separator)?;
+    let looks_grouped = frac_part.len() == 3 && !int_part.trim_start_matches('0').is_empty();
+    (!looks_grouped).then_some(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_common_formats() {
+        assert_eq!(parse_amount("1,234.56").unwrap(), 1234.56);
+        assert_eq!(parse_amount("1.234,56").unwrap(), 1234.56);
+        assert_eq!(parse_amount("1 000,50").unwrap(), 1000.5);
+        assert_eq!(parse_amount("1\u{a0}000,50").unwrap(), 1000.5);
+        assert_eq!(parse_amount("1'234.50").unwrap(), 1234.5);
+        assert_eq!(parse_amount("12.345.678").unwrap(), 12345678.0);
+        assert_eq!(parse_amount(" 42 ").unwrap(), 42.0);
+        assert_eq!(parse_amount("-12,5").unwrap(), -12.5);
+        assert_eq!(parse_amount(".5").unwrap(), 0.5);
+    }
+
+    #[test]
+    fn resolves_single_separator_heuristically() {
+        assert_eq!(parse_amount("1,234").unwrap(), 1234.0);
+        assert_eq!(parse_amount("1.234").unwrap(), 1234.0);
+        assert_eq!(parse_amount("0,125").unwrap(), 0.125);
+        assert_eq!(parse_amount("12,50").unwrap(), 12.5);
+        assert_eq!(parse_amount("1.5").unwrap(), 1.5);
+    }
+
+    #[test]
+    fn formats_with_symbol_and_grouping() {
+        assert_eq!(format_amount(1234.56, "eur"), "€1,234.56");
+        assert_eq!(format_amount(1234567.891, "usd"), "$1,234,567.89");
+        assert_eq!(format_amount(0.5, "gbp"), "£0.50");
+        assert_eq!(format_amount(-1005.125, "usd"), "-$1,005.13");
+        assert_eq!(format_amount(123.0, "usd"), "$123.00");
+    }
+
+    #[test]
+    fn formats_zero_decimal_currencies() {
+        assert_eq!(format_amount(1234.5, "jpy"), "¥1,235");
+        assert_eq!(format_amount(999.4, "krw"), "₩999");
+    }
+
+    #[test]
+    fn falls_back_to_code_for_unknown_symbols() {
+        assert_eq!(format_amount(1234.555, "chf"), "CHF 1234.56");
+        assert_eq!(format_amount(0.1, "btc"), "BTC 0.10");
+    }
+
+    #[test]
+    fn rejects_letters_and_malformed_input() {
+        for input in ["12abc", "1e5", "$100", "", "  ", ",", "1.234,56,7", "--5", "EUR 10"] {
+            let err = parse_amount(input).unwrap_err();
+            assert_eq!(err.to_string(), format!("Invalid amount: {}", input));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::schema::UnexpectedSchema;
+use std::fmt;
+use std::string::FromUtf8Error;
+
+/// What went wrong, as reported across the WIT boundary. The error string is
+/// `"<kind>: <detail>"` with a stable kind prefix, so callers can decide
+/// whether to retry or surface the message without parsing the detail.
+#[derive(Debug, PartialEq)]
+pub enum PluginError {
+    /// Every endpoint was unreachable or kept failing (5xx) after its
+    /// retries.
+    Network(String),
+    /// The last endpoint tried was still answering HTTP 429 after its retries.
+    RateLimited(String),
+    /// An endpoint answered with a non-retryable status not covered below.
+    HttpStatus(u16, String),
+    /// The response was not the JSON shape we expected.
+    Parse(String),
+    /// An argument or snapshot was rejected before any request.
+    InvalidInput(String),
+    /// The currency, pair or date has no published rates (HTTP 404 or missing
+    /// from the rate table).
+    NotFound(String),
+}
+
+impl PluginError {
+    /// The stable prefix of the WIT error string.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PluginError::Network(_) => "network",
+            PluginError::RateLimited(_) => "rate_limited",
+            PluginError::HttpStatus(..) => "http_status",
+            PluginError::Parse(_) => "parse",
+            PluginError::InvalidInput(_) => "invalid_input",
+            PluginError::NotFound(_) => "not_found",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match self {
+            PluginError::Network(detail)
+            | PluginError::RateLimited(detail)
+            | PluginError::HttpStatus(_, detail)
+            | PluginError::Parse(detail)
+            | PluginError::InvalidInput(detail)
+            | PluginError::NotFound(detail) => detail,
+        }
+    }
+
+    /// The same kind of error with its detail rewritten, for callers that
+    /// explain which lookup failed.
+    pub fn map_detail(self, f: impl FnOnce(&str) -> String) -> Self {
+        match self {
+            PluginError::Network(detail) => PluginError::Network(f(&detail)),
+            PluginError::RateLimited(detail) => PluginError::RateLimited(f(&detail)),
+            PluginError::HttpStatus(status, detail) => PluginError::HttpStatus(status, f(&detail)),
+            PluginError::Parse(detail) => PluginError::Parse(f(&detail)),
+            PluginError::InvalidInput(detail) => PluginError::InvalidInput(f(&detail)),
+            PluginError::NotFound(detail) => PluginError::NotFound(f(&detail)),
+        }
+    }
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.detail())
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// Classify an error from an internal function. Errors raised without a kind
+/// are argument checks, so they are reported as `invalid_input`.
+impl From<anyhow::Error> for PluginError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<PluginError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let detail = err.to_string();
+        let unparseable = err
+            .chain()
+            .any(|cause| cause.is::<serde_json::Error>() || cause.is::<FromUtf8Error>() || cause.is::<UnexpectedSchema>());
+        if unparseable {
+            PluginError::Parse(detail)
+        } else {
+            PluginError::InvalidInput(detail)
+        }
+    }
+}
+
+/// The WIT error string for an error from an internal function, with `what`
+/// failed leading the detail, e.g. `"network: Exchange rate request failed: ..."`.
+/// Logged first when `NOORLE_DEBUG` is on.
+pub fn to_wit(err: anyhow::Error, what: &str) -> String {
+    let err = PluginError::from(err);
+    let detail = format!("{}: {}", what, err.detail());
+    plugin_http::debug_log!("error", kind = err.kind(), detail = detail);
+    format!("{}: {}", err.kind(), detail)
+}
+
+/// The WIT error string for `err`, logged first when `NOORLE_DEBUG` is on.
+pub fn report(err: PluginError) -> String {
+    plugin_http::debug_log!("error", kind = err.kind(), detail = err.detail());
+    err.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn prefixes_wit_errors_with_a_stable_kind() {
+        let not_found = anyhow::Error::from(PluginError::NotFound("Exchange rate API returned status code: 404".to_string()));
+        assert_eq!(
+            to_wit(not_found, "Exchange rate request failed"),
+            "not_found: Exchange rate request failed: Exchange rate API returned status code: 404"
+        );
+
+        let parse = serde_json::from_str::<serde_json::Value>("{").context("Failed to parse JSON response").unwrap_err();
+        assert_eq!(to_wit(parse, "Quote failed"), "parse: Quote failed: Failed to parse JSON response");
+
+        let invalid = anyhow::anyhow!("Invalid amount: ten");
+        assert_eq!(PluginError::from(invalid).to_string(), "invalid_input: Invalid amount: ten");
+    }
+
+    #[test]
+    fn rewrites_detail_keeping_the_kind() {
+        let err = PluginError::Network("All 3 API endpoints failed".to_string())
+            .map_detail(|detail| format!("No usd to eur rate for 2024-01-02 ({})", detail));
+        assert_eq!(err.to_string(), "network: No usd to eur rate for 2024-01-02 (All 3 API endpoints failed)");
+    }
+}
@@ -1,196 +1,902 @@
 #![allow(unsafe_op_in_unsafe_fn)]
 
+mod aliases;
+mod amount;
+mod error;
+mod quote;
+mod rate_cache;
+mod regions;
+mod retry;
+mod schema;
+mod snapshot;
 mod types;
 
 use anyhow::{Context, Result};
-use serde_json::Value;
-use std::collections::HashMap;
-use std::time::Duration;
-use types::{ConversionResponse, CurrencyListResponse, ExchangeRateResponse};
-use waki::Client;
-
-wit_bindgen::generate!({
-    world: "exchange-rate-component",
-    path: "./wit",
-});
-
-const PRIMARY_ENDPOINT: &str = "https://cdn.jsdelivr.net/npm/@fawazahmed0/currency-api@latest/v1/currencies";
-const FALLBACK_ENDPOINT: &str = "https://latest.currency-api.pages.dev/v1/currencies";
+use chrono::{DateTime, NaiveDate, Utc};
+use error::PluginError;
+use plugin_http::HttpError;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use snapshot::RateSnapshot;
+use std::time::{Duration, Instant};
+use types::{
+    AverageConversionResponse, BatchConversionItem, BatchConversionResponse, ConversionError, ConversionResponse,
+    CurrencyListResponse, CurrencyRegionsResponse, DetailedExchangeRateResponse, DetailedRate, ExchangeRateResponse,
+    PairRateResponse, QuoteResponse, RateChangeResponse, RegionGroup, SplitResponse,
+};
+
+mod bindings {
+    // The generated export shims take one argument per flattened WIT parameter.
+    #![allow(clippy::too_many_arguments)]
+
+    wit_bindgen::generate!({
+        world: "exchange-rate-component",
+        path: "./wit",
+    });
+}
+
+use bindings::*;
+
+/// Dataset version tag for the most recent rates.
+const LATEST: &str = "latest";
 const TIMEOUT_SECS: u64 = 30;
+/// Environment variable overriding `TIMEOUT_SECS`.
+const TIMEOUT_VAR: &str = "EXCHANGE_RATE_TIMEOUT_SECS";
+const MAX_AVERAGE_DAYS: u32 = 30;
+const HISTORY_FETCH_DELAY_MS: u64 = 200;
+const MAX_SPLIT_WAYS: u32 = 1000;
+/// Larger `decimal_places` requests are clamped to this.
+const MAX_DECIMAL_PLACES: u32 = 10;
+/// Currency used for a two-hop conversion when a pair has no direct rate.
+const INTERMEDIARY: &str = "usd";
+/// Split amounts are settled in hundredths of the target currency.
+const MINOR_UNITS: f64 = 100.0;
+
+/// Rates for `base_currency`, limited to `targets` unless that is empty.
+/// The base and targets may be aliases such as `"euro"` or `"£"`; they are
+/// resolved, trimmed and lowercased here, so callers can pass them as given.
+fn get_exchange_rates_internal(
+    base_currency: String,
+    targets: Vec<String>,
+    majors_only: bool,
+    with_metrics: bool,
+    include_names: bool,
+) -> Result<ExchangeRateResponse> {
+    let base_currency = aliases::resolve(&base_currency)?;
+    let targets = targets
+        .iter()
+        .map(|target| aliases::resolve(target))
+        .collect::<Result<Vec<_>>>()?;
+    let targets = normalize_targets(targets);
+
+    let started = Instant::now();
+    let exchange_data = fetch_rate_table(&base_currency, LATEST)?;
+    let elapsed_ms = u32::try_from(started.elapsed().as_millis()).unwrap_or(u32::MAX);
+
+    let (date, all_rates) = schema::rate_table(&exchange_data, &base_currency)?;
+    let last_updated = date.unwrap_or("unknown").to_string();
 
-fn get_exchange_rates_internal(base_currency: String, target_currencies: String) -> Result<ExchangeRateResponse> {
-    let base_currency = base_currency.to_lowercase();
-
-    let encoded_base = urlencoding::encode(&base_currency);
-
-    let request_url = format!("{}/{}.json", PRIMARY_ENDPOINT, encoded_base);
-
-    let response = Client::new()
-        .get(&request_url)
-        .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-        .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-        .send()
-        .or_else(|_| {
-            let fallback_url = format!("{}/{}.json", FALLBACK_ENDPOINT, encoded_base);
-            Client::new()
-                .get(&fallback_url)
-                .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-                .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-                .send()
+    let rates = select_rates(all_rates, &targets, majors_only);
+    // Names are a convenience: if the currency list can't be fetched, still return the rates.
+    let names = include_names.then(|| {
+        list_currencies_internal()
+            .map(|list| names_for(&rates, list.currencies))
+            .unwrap_or_default()
+    });
+
+    Ok(ExchangeRateResponse {
+        base_currency,
+        rates,
+        names,
+        unknown_currencies: unknown_targets(all_rates, &targets),
+        last_updated_ts: date.and_then(date_timestamp),
+        last_updated,
+        elapsed_ms: with_metrics.then_some(elapsed_ms),
+    })
+}
+
+/// `get-exchange-rates` with every returned rate labeled crypto or fiat using
+/// the bundled ticker list.
+fn get_exchange_rates_detailed_internal(base_currency: String, target_currencies: String) -> Result<DetailedExchangeRateResponse> {
+    let response = get_exchange_rates_internal(base_currency, parse_targets(&target_currencies), false, false, false)?;
+    Ok(label_rates(response))
+}
+
+fn label_rates(response: ExchangeRateResponse) -> DetailedExchangeRateResponse {
+    let rates = response
+        .rates
+        .into_iter()
+        .map(|(code, rate)| {
+            let is_crypto = regions::is_crypto(&code);
+            (code, DetailedRate { rate, is_crypto })
         })
-        .context("Both primary and fallback API requests failed")?;
+        .collect();
 
-    let status = response.status_code();
-    if !(200..300).contains(&status) {
-        anyhow::bail!("Exchange rate API returned status code: {}", status);
+    DetailedExchangeRateResponse {
+        base_currency: response.base_currency,
+        rates,
+        last_updated: response.last_updated,
+        last_updated_ts: response.last_updated_ts,
+        unknown_currencies: response.unknown_currencies,
     }
+}
 
-    let body_bytes = response.body()
-        .context("Failed to read response body")?;
+/// The latest rate for one pair, fetched from the narrow `<base>/<target>.json`
+/// endpoint so the full table isn't downloaded.
+fn get_pair_rate_internal(base_currency: String, target_currency: String) -> Result<PairRateResponse> {
+    let base = base_currency.trim().to_lowercase();
+    let target = target_currency.trim().to_lowercase();
 
-    let body = String::from_utf8(body_bytes)
-        .context("Invalid UTF-8 in response")?;
+    let (date, rate) = pair_rate_at(&base, &target, LATEST)?;
+    // A same-currency pair is answered without a request, like `convert-currency`.
+    let no_date = if base == target { "N/A" } else { "unknown" };
 
-    let exchange_data: Value = serde_json::from_str(&body)
-        .context("Failed to parse JSON response")?;
+    Ok(PairRateResponse {
+        last_updated: date.unwrap_or_else(|| no_date.to_string()),
+        base,
+        target,
+        rate,
+    })
+}
 
-    let last_updated = exchange_data["date"]
-        .as_str()
-        .unwrap_or("unknown")
-        .to_string();
+/// `(date, rate)` for one pair at `version` (`LATEST` or `YYYY-MM-DD`).
+///
+/// Tries the per-pair endpoint first; if it has no such pair (404), reads the
+/// rate from the (possibly cached) full table instead.
+fn pair_rate_at(base: &str, target: &str, version: &str) -> Result<(Option<String>, f64)> {
+    if base == target {
+        return Ok((None, 1.0));
+    }
 
-    let all_rates = exchange_data[&base_currency]
-        .as_object()
-        .ok_or_else(|| anyhow::anyhow!("No exchange rates found in response"))?;
+    let pair_path = format!("{}/{}.json", urlencoding::encode(base), urlencoding::encode(target));
+    pair_or_table_rate(request_currency_json(version, &pair_path), target, || {
+        let data = fetch_rate_table(base, version)?;
+        let (date, rates) = schema::rate_table(&data, base)?;
+        let rate = rates
+            .get(target)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| PluginError::NotFound(format!("Exchange rate not found for {} to {}", base, target)))?;
+        Ok((date.map(str::to_string), rate))
+    })
+}
 
-    let mut rates = HashMap::new();
+/// `(date, rate)` from the per-pair endpoint's response, or from `table_rate`
+/// when the endpoint has no such pair. Any other failure (exhausted retries,
+/// rate limiting, a bad payload) is returned as is, so a struggling host isn't
+/// hit again with the heavier table request.
+fn pair_or_table_rate(
+    pair: Result<Value>,
+    target: &str,
+    table_rate: impl FnOnce() -> Result<(Option<String>, f64)>,
+) -> Result<(Option<String>, f64)> {
+    match pair {
+        Ok(data) => {
+            let (date, rate) = schema::pair_rate(&data, target)?;
+            Ok((date.map(str::to_string), rate))
+        }
+        Err(e) if matches!(e.downcast_ref::<PluginError>(), Some(PluginError::NotFound(_))) => table_rate(),
+        Err(e) => Err(e),
+    }
+}
 
-    if !target_currencies.is_empty() {
-        let target_list: Vec<String> = target_currencies
-            .split(',')
-            .map(|s| s.trim().to_lowercase())
-            .filter(|s| !s.is_empty())
-            .collect();
+/// How a pair's rate moved between two dates.
+fn rate_change_internal(
+    base_currency: String,
+    target_currency: String,
+    start_date: String,
+    end_date: String,
+) -> Result<RateChangeResponse> {
+    let base = base_currency.trim().to_lowercase();
+    let target = target_currency.trim().to_lowercase();
 
-        for target in target_list {
-            if let Some(rate_value) = all_rates.get(&target) {
-                if let Some(rate) = rate_value.as_f64() {
-                    rates.insert(target, rate);
-                }
-            }
+    let today = Utc::now().date_naive();
+    let start_date = validate_history_date(start_date.trim(), today)?;
+    let end_date = validate_history_date(end_date.trim(), today)?;
+    // ISO dates order the same as strings.
+    if start_date > end_date {
+        anyhow::bail!("Start date {} is after end date {}", start_date, end_date);
+    }
+
+    let (_, start_rate) = pair_rate_at(&base, &target, &start_date)
+        .map_err(|e| PluginError::from(e).map_detail(|e| format!("No {} to {} rate for {} ({})", base, target, start_date, e)))?;
+    let (_, end_rate) = pair_rate_at(&base, &target, &end_date)
+        .map_err(|e| PluginError::from(e).map_detail(|e| format!("No {} to {} rate for {} ({})", base, target, end_date, e)))?;
+
+    let (absolute_change, percent_change) = change_between(start_rate, end_rate);
+
+    Ok(RateChangeResponse {
+        base,
+        target,
+        start_date,
+        end_date,
+        start_rate,
+        end_rate,
+        absolute_change,
+        percent_change,
+    })
+}
+
+/// `(end - start, percent change)`; the percentage is `None` when `start` is 0.
+fn change_between(start: f64, end: f64) -> (f64, Option<f64>) {
+    let absolute = end - start;
+    let percent = (start != 0.0).then(|| absolute / start * 100.0);
+    (absolute, percent)
+}
+
+/// Rates for `base_currency` as published on `date` (`YYYY-MM-DD`), in the
+/// `get-exchange-rates` shape with `last_updated` set to the snapshot's date.
+fn get_historical_rates_internal(base_currency: String, target_currencies: String, date: String) -> Result<ExchangeRateResponse> {
+    let base_currency = base_currency.trim().to_lowercase();
+    let date = validate_history_date(date.trim(), Utc::now().date_naive())?;
+
+    let exchange_data = fetch_rate_table(&base_currency, &date)
+        .map_err(|e| PluginError::from(e).map_detail(|e| format!("No {} rates available for {} ({})", base_currency, date, e)))?;
+
+    let (snapshot_date, all_rates) = schema::rate_table(&exchange_data, &base_currency)?;
+    let last_updated = snapshot_date.unwrap_or(&date);
+    let targets = parse_targets(&target_currencies);
+
+    Ok(ExchangeRateResponse {
+        base_currency,
+        rates: select_rates(all_rates, &targets, false),
+        names: None,
+        unknown_currencies: unknown_targets(all_rates, &targets),
+        last_updated: last_updated.to_string(),
+        last_updated_ts: date_timestamp(last_updated),
+        elapsed_ms: None,
+    })
+}
+
+/// Check that `date` is a real `YYYY-MM-DD` date no later than `today`.
+fn validate_history_date(date: &str, today: NaiveDate) -> Result<String> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .filter(|parsed| parsed.format("%Y-%m-%d").to_string() == date)
+        .ok_or_else(|| anyhow::anyhow!("Invalid date '{}': expected YYYY-MM-DD", date))?;
+
+    if parsed > today {
+        anyhow::bail!("Date {} is in the future; the latest available is {}", date, today);
+    }
+
+    Ok(date.to_string())
+}
+
+/// Pick the numeric rates to return: the comma-separated `target_currencies`
+/// if any are given, otherwise the whole table, optionally narrowed to majors.
+fn select_rates(all_rates: &Map<String, Value>, targets: &[String], majors_only: bool) -> HashMap<String, f64> {
+    all_rates
+        .iter()
+        .filter(|(currency, _)| targets.is_empty() || targets.contains(currency))
+        .filter(|(currency, _)| !majors_only || regions::MAJORS.contains(&currency.as_str()))
+        .filter_map(|(currency, rate)| rate.as_f64().map(|rate| (currency.clone(), rate)))
+        .collect()
+}
+
+/// Full names for just the currencies in `rates`.
+fn names_for(rates: &HashMap<String, f64>, mut currencies: HashMap<String, String>) -> HashMap<String, String> {
+    currencies.retain(|code, _| rates.contains_key(code));
+    currencies
+}
+
+/// Requested targets with no usable rate in the table, in request order.
+fn unknown_targets(all_rates: &Map<String, Value>, targets: &[String]) -> Vec<String> {
+    let mut unknown: Vec<String> = Vec::new();
+    for target in targets {
+        let known = all_rates.get(target).and_then(Value::as_f64).is_some();
+        if !known && !unknown.contains(target) {
+            unknown.push(target.clone());
+        }
+    }
+    unknown
+}
+
+/// Lowercased, trimmed codes from a comma-separated target list.
+fn parse_targets(target_currencies: &str) -> Vec<String> {
+    normalize_targets(target_currencies.split(',').map(str::to_string).collect())
+}
+
+/// Trim and lowercase each target code, dropping empty entries.
+fn normalize_targets(targets: Vec<String>) -> Vec<String> {
+    targets
+        .into_iter()
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn convert_currency_internal(from_currency: String, to_currency: String, amount: f64, pivot_currency: String) -> Result<ConversionResponse> {
+    let from_currency = aliases::resolve(&from_currency)?;
+    let to_currency = aliases::resolve(&to_currency)?;
+    let pivot_currency = aliases::resolve(&pivot_currency)?;
+
+    if from_currency == to_currency {
+        return compute_conversion(&Map::new(), &from_currency, &to_currency, amount, "N/A");
+    }
+
+    let exchange_data = fetch_rate_table(&from_currency, LATEST)?;
+
+    let (date, rates) = schema::rate_table(&exchange_data, &from_currency)?;
+    let last_updated = date.unwrap_or("unknown");
+
+    if pivot_currency.is_empty() {
+        let direct = compute_conversion(rates, &from_currency, &to_currency, amount, last_updated);
+        // Only a missing quote is worth a second hop; a malformed one stays an error.
+        let needs_hop = direct.is_err()
+            && !rates.contains_key(&to_currency)
+            && rates.contains_key(INTERMEDIARY)
+            && from_currency != INTERMEDIARY
+            && to_currency != INTERMEDIARY;
+        if !needs_hop {
+            return direct;
         }
+
+        let via_data = fetch_rate_table(INTERMEDIARY, LATEST)?;
+        let (_, via_rates) = schema::rate_table(&via_data, INTERMEDIARY)?;
+        // If the second hop is missing too, report the original direct error.
+        return compute_via_conversion(rates, via_rates, &from_currency, INTERMEDIARY, &to_currency, amount, last_updated)
+            .or(direct);
+    }
+
+    if pivot_currency == from_currency || pivot_currency == to_currency {
+        // Routing through either end of the pair is just the direct quote.
+        let mut conversion = compute_conversion(rates, &from_currency, &to_currency, amount, last_updated)?;
+        conversion.route = Some("direct".to_string());
+        return Ok(conversion);
+    }
+
+    let pivot_data = fetch_rate_table(&pivot_currency, LATEST)?;
+    let (_, pivot_rates) = schema::rate_table(&pivot_data, &pivot_currency)?;
+
+    compute_pivot_conversion(rates, pivot_rates, &from_currency, &pivot_currency, &to_currency, amount, last_updated)
+}
+
+/// Convert several `(to_currency, amount)` lines from one currency with a single
+/// rate-table fetch. A target missing from the table fails only its own line.
+fn convert_currency_batch_internal(from_currency: String, conversions: Vec<(String, f64)>) -> Result<BatchConversionResponse> {
+    let from_currency = from_currency.trim().to_lowercase();
+    let conversions: Vec<(String, f64)> = conversions
+        .into_iter()
+        .map(|(to_currency, amount)| (to_currency.trim().to_lowercase(), amount))
+        .collect();
+
+    // Same-currency lines convert at 1.0, so only fetch when some line needs a rate.
+    let needs_rates = conversions.iter().any(|(to_currency, _)| *to_currency != from_currency);
+    let exchange_data = if needs_rates {
+        Some(fetch_rate_table(&from_currency, LATEST)?)
     } else {
-        for (currency, rate_value) in all_rates {
-            if let Some(rate) = rate_value.as_f64() {
-                rates.insert(currency.clone(), rate);
-            }
+        None
+    };
+
+    let no_rates = Map::new();
+    let (last_updated, rates) = match &exchange_data {
+        Some(data) => {
+            let (date, rates) = schema::rate_table(data, &from_currency)?;
+            (date.unwrap_or("unknown"), rates)
         }
+        None => ("N/A", &no_rates),
+    };
+
+    let items = convert_lines(rates, &from_currency, conversions, last_updated);
+    Ok(BatchConversionResponse { batch_total: batch_total(&items), items })
+}
+
+/// The sum of every line's `converted_amount`, or `None` if the lines target
+/// different currencies (their amounts can't be added), any line failed, or
+/// there are no lines.
+fn batch_total(items: &[BatchConversionItem]) -> Option<f64> {
+    let targets: HashSet<&str> = items.iter().map(BatchConversionItem::to_currency).collect();
+    let mixed_targets = targets.len() > 1;
+    if items.is_empty() || mixed_targets {
+        return None;
     }
 
-    Ok(ExchangeRateResponse {
-        base_currency,
-        rates,
-        last_updated,
+    items
+        .iter()
+        .map(|item| match item {
+            BatchConversionItem::Converted(conversion) => Some(conversion.converted_amount),
+            BatchConversionItem::Failed(_) => None,
+        })
+        .sum()
+}
+
+fn convert_lines(
+    rates: &Map<String, Value>,
+    from_currency: &str,
+    conversions: Vec<(String, f64)>,
+    last_updated: &str,
+) -> Vec<BatchConversionItem> {
+    conversions
+        .into_iter()
+        .map(|(to_currency, amount)| {
+            match compute_conversion(rates, from_currency, &to_currency, amount, last_updated) {
+                Ok(conversion) => BatchConversionItem::Converted(conversion),
+                Err(e) => BatchConversionItem::Failed(ConversionError {
+                    from_currency: from_currency.to_string(),
+                    to_currency,
+                    amount,
+                    error: e.to_string(),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Convert at the latest rate and attach a human-readable quote and stable quote id.
+fn generate_quote_internal(from_currency: String, to_currency: String, amount: f64) -> Result<QuoteResponse> {
+    let conversion = convert_currency_internal(from_currency, to_currency, amount, String::new())?;
+
+    Ok(QuoteResponse {
+        quote: quote::format_quote(&conversion),
+        quote_id: quote::quote_id(&conversion),
+        conversion,
     })
 }
 
-fn convert_currency_internal(from_currency: String, to_currency: String, amount: f64) -> Result<ConversionResponse> {
-    let from_currency = from_currency.to_lowercase();
-    let to_currency = to_currency.to_lowercase();
+/// Convert `amount` at the mean rate over the last `days` daily snapshots.
+///
+/// Snapshots that cannot be fetched (e.g. today's, before it is published)
+/// are skipped; the response reports how many days actually contributed.
+fn convert_currency_avg_internal(from_currency: String, to_currency: String, amount: f64, days: u32) -> Result<AverageConversionResponse> {
+    let from_currency = from_currency.trim().to_lowercase();
+    let to_currency = to_currency.trim().to_lowercase();
+    let days = days.clamp(1, MAX_AVERAGE_DAYS);
+
+    let dates = recent_dates(Utc::now().date_naive(), days);
 
     if from_currency == to_currency {
-        return Ok(ConversionResponse {
+        return Ok(AverageConversionResponse {
             from_currency,
             to_currency,
             amount,
             converted_amount: amount,
-            exchange_rate: 1.0,
-            last_updated: "N/A".to_string(),
+            average_rate: 1.0,
+            days_requested: days,
+            days_available: days,
+            start_date: dates.last().cloned(),
+            end_date: dates.first().cloned(),
         });
     }
 
-    let encoded_from = urlencoding::encode(&from_currency);
-    let request_url = format!("{}/{}.json", PRIMARY_ENDPOINT, encoded_from);
-
-    let response = Client::new()
-        .get(&request_url)
-        .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-        .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-        .send()
-        .or_else(|_| {
-            let fallback_url = format!("{}/{}.json", FALLBACK_ENDPOINT, encoded_from);
-            Client::new()
-                .get(&fallback_url)
-                .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-                .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-                .send()
-        })
-        .context("Both primary and fallback API requests failed")?;
+    let mut first = true;
+    let (sampled_dates, samples): (Vec<String>, Vec<f64>) = collect_samples(&dates, |date| {
+        if !std::mem::take(&mut first) {
+            std::thread::sleep(Duration::from_millis(HISTORY_FETCH_DELAY_MS));
+        }
+
+        let snapshot = fetch_rate_table(&from_currency, date)?;
+        let (_, rates) = schema::rate_table(&snapshot, &from_currency)?;
+        Ok(rates.get(&to_currency).and_then(Value::as_f64))
+    })?
+    .into_iter()
+    .unzip();
+
+    let average_rate = mean(&samples).ok_or_else(|| {
+        PluginError::NotFound(format!("No {} to {} rates available in the last {} days", from_currency, to_currency, days))
+    })?;
+
+    Ok(AverageConversionResponse {
+        from_currency,
+        to_currency,
+        amount,
+        converted_amount: amount * average_rate,
+        average_rate,
+        days_requested: days,
+        days_available: samples.len() as u32,
+        start_date: sampled_dates.last().cloned(),
+        end_date: sampled_dates.first().cloned(),
+    })
+}
+
+/// Convert a total and split it `ways` ways in the target currency.
+///
+/// The converted total is rounded to two decimals and divided in minor units;
+/// any leftover cents go one each to the first shares, so the shares always
+/// sum exactly to `converted_amount`.
+fn convert_and_split_internal(from_currency: String, to_currency: String, amount: f64, ways: u32) -> Result<SplitResponse> {
+    if ways == 0 {
+        anyhow::bail!("Cannot split between 0 people; ways must be at least 1");
+    }
+    if ways > MAX_SPLIT_WAYS {
+        anyhow::bail!("Cannot split more than {} ways", MAX_SPLIT_WAYS);
+    }
+    if !amount.is_finite() || amount < 0.0 {
+        anyhow::bail!("Amount to split must be a non-negative number");
+    }
+
+    let conversion = convert_currency_internal(from_currency, to_currency, amount, String::new())?;
+
+    let total_minor = (conversion.converted_amount * MINOR_UNITS).round() as u64;
+    let shares = split_minor_units(total_minor, ways)
+        .into_iter()
+        .map(|share| share as f64 / MINOR_UNITS)
+        .collect();
+
+    Ok(SplitResponse {
+        from_currency: conversion.from_currency,
+        to_currency: conversion.to_currency,
+        amount,
+        exchange_rate: conversion.exchange_rate,
+        converted_amount: total_minor as f64 / MINOR_UNITS,
+        ways,
+        shares,
+        last_updated: conversion.last_updated,
+    })
+}
 
-    let status = response.status_code();
-    if !(200..300).contains(&status) {
-        anyhow::bail!("Exchange rate API returned status code: {}", status);
+/// Round `converted_amount` to `decimal_places` (clamped to `MAX_DECIMAL_PLACES`),
+/// leaving the rates at full precision. `None` leaves the conversion untouched.
+fn with_decimal_places(mut conversion: ConversionResponse, decimal_places: Option<u32>) -> ConversionResponse {
+    if let Some(places) = decimal_places {
+        let places = places.min(MAX_DECIMAL_PLACES) as usize;
+        conversion.converted_amount = round_half_up(conversion.converted_amount, places);
+        conversion.round_trip_amount = conversion.round_trip_amount.map(|amount| round_half_up(amount, places));
     }
+    conversion
+}
+
+/// Add `formatted_amount`, the converted amount in the target currency's
+/// display format, when `include` is set. The numeric fields are unchanged.
+fn with_formatted_amount(mut conversion: ConversionResponse, include: bool) -> ConversionResponse {
+    if include {
+        conversion.formatted_amount = Some(amount::format_amount(conversion.converted_amount, &conversion.to_currency));
+    }
+    conversion
+}
 
-    let body_bytes = response.body()
-        .context("Failed to read response body")?;
+/// Convert `converted_amount` back to the source currency using the target
+/// currency's own rate table (usually a cache hit when called right after a
+/// conversion), so spreads between the two published tables show up.
+fn with_round_trip(conversion: ConversionResponse) -> Result<ConversionResponse> {
+    if conversion.from_currency == conversion.to_currency {
+        return apply_round_trip(conversion, &Map::new());
+    }
+
+    let reverse_data = fetch_rate_table(&conversion.to_currency, LATEST)?;
+    let (_, reverse_rates) = schema::rate_table(&reverse_data, &conversion.to_currency)?;
+    apply_round_trip(conversion, reverse_rates)
+}
+
+fn apply_round_trip(mut conversion: ConversionResponse, reverse_rates: &Map<String, Value>) -> Result<ConversionResponse> {
+    let back = compute_conversion(
+        reverse_rates,
+        &conversion.to_currency,
+        &conversion.from_currency,
+        conversion.converted_amount,
+        &conversion.last_updated,
+    )?;
+    conversion.round_trip_amount = Some(back.converted_amount);
+    Ok(conversion)
+}
+
+/// Round to `places` decimals, with halves rounding away from zero.
+///
+/// Works on the shortest decimal representation of `value` rather than scaling by
+/// a power of ten, so `1.005` rounds to `1.01` and amounts too large to have a
+/// fractional part keep every integer digit.
+fn round_half_up(value: f64, places: usize) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+
+    // `{}` never uses exponent notation for f64, so this is plain digits and a point.
+    let text = format!("{}", value.abs());
+    let (int_part, frac_part) = text.split_once('.').unwrap_or((&text, ""));
+    if frac_part.len() <= places {
+        return value;
+    }
+
+    let mut digits: Vec<u8> = int_part.bytes().chain(frac_part.bytes().take(places)).collect();
+    if frac_part.as_bytes()[places] >= b'5' {
+        // Propagate the carry; a leading carry adds a new digit ("9.99" -> "10.00").
+        let mut i = digits.len();
+        loop {
+            if i == 0 {
+                digits.insert(0, b'1');
+                break;
+            }
+            i -= 1;
+            if digits[i] == b'9' {
+                digits[i] = b'0';
+            } else {
+                digits[i] += 1;
+                break;
+            }
+        }
+    }
+
+    let point = digits.len() - places;
+    let rounded = format!(
+        "{}.{}",
+        String::from_utf8_lossy(&digits[..point]),
+        String::from_utf8_lossy(&digits[point..])
+    );
+    let rounded: f64 = rounded.parse().unwrap_or(value.abs());
+    rounded.copysign(value)
+}
+
+/// Divide `total` into `ways` near-equal parts, largest first, that sum to `total`.
+fn split_minor_units(total: u64, ways: u32) -> Vec<u64> {
+    let ways = u64::from(ways);
+    let base = total / ways;
+    let remainder = total % ways;
+    (0..ways).map(|i| base + u64::from(i < remainder)).collect()
+}
+
+/// The `days` most recent calendar dates ending at `today`, newest first, as `YYYY-MM-DD`.
+fn recent_dates(today: NaiveDate, days: u32) -> Vec<String> {
+    (0..days as i64)
+        .map(|offset| (today - chrono::Duration::days(offset)).format("%Y-%m-%d").to_string())
+        .collect()
+}
+
+/// Sample the rate for each date, keeping `(date, rate)` for the dates that have one.
+///
+/// A date whose snapshot is missing (`NotFound`) or lacks the pair is skipped.
+/// Other failures are skipped too while any date yields a rate; when none does,
+/// the last one is returned so an outage, rate limit or schema change keeps its kind.
+fn collect_samples(dates: &[String], mut sample: impl FnMut(&str) -> Result<Option<f64>>) -> Result<Vec<(String, f64)>> {
+    let mut samples = Vec::new();
+    let mut last_error = None;
+    for date in dates {
+        match sample(date) {
+            Ok(Some(rate)) => samples.push((date.clone(), rate)),
+            Ok(None) => {}
+            Err(e) if matches!(e.downcast_ref::<PluginError>(), Some(PluginError::NotFound(_))) => {}
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    match last_error {
+        Some(e) if samples.is_empty() => Err(e),
+        _ => Ok(samples),
+    }
+}
+
+fn mean(samples: &[f64]) -> Option<f64> {
+    if samples.is_empty() {
+        None
+    } else {
+        Some(samples.iter().sum::<f64>() / samples.len() as f64)
+    }
+}
+
+/// Fetch and parse the rate table for `base_currency` from the primary
+/// endpoint, falling back to the mirrors in turn. Tables fetched within the
+/// cache TTL are reused without a request.
+///
+/// `version` is `LATEST` or a `YYYY-MM-DD` snapshot date.
+fn fetch_rate_table(base_currency: &str, version: &str) -> Result<Value> {
+    rate_cache::cached(base_currency, version, || request_rate_table(base_currency, version))
+}
+
+fn request_rate_table(base_currency: &str, version: &str) -> Result<Value> {
+    request_currency_json(version, &format!("{}.json", urlencoding::encode(base_currency)))
+}
+
+/// GET `path` under the currencies endpoint for `version`, trying the primary
+/// host and then each mirror, and parse the body as JSON.
+fn request_currency_json(version: &str, path: &str) -> Result<Value> {
+    let body_bytes = fetch_with_fallbacks(&endpoint_urls(version, &format!("/{}", path)), "Exchange rate API")?;
 
     let body = String::from_utf8(body_bytes)
         .context("Invalid UTF-8 in response")?;
 
-    let exchange_data: Value = serde_json::from_str(&body)
-        .context("Failed to parse JSON response")?;
+    serde_json::from_str(&body)
+        .context("Failed to parse JSON response")
+}
 
-    let last_updated = exchange_data["date"]
-        .as_str()
-        .unwrap_or("unknown")
-        .to_string();
+/// GET each URL in order until one responds, retrying transient failures on each.
+///
+/// A non-retryable error status (a 4xx other than 429) is final and reported as
+/// `<what> returned status code: <status>`, as `NotFound` for a 404; only
+/// exhausted retries move on to the next URL.
+fn fetch_with_fallbacks(urls: &[String], what: &str) -> Result<Vec<u8>> {
+    let timeout = request_timeout();
+    let retries = retry::max_attempts() - 1;
 
-    let rates = exchange_data[&from_currency]
-        .as_object()
-        .ok_or_else(|| anyhow::anyhow!("No exchange rates found in response"))?;
+    first_success(urls, |url| match plugin_http::get_with_retry(url, &[], timeout, retries) {
+        Err(HttpError::Status { status, .. }) if !plugin_http::is_retryable_status(status) => Ok(Err(status)),
+        result => result.map(Ok),
+    })?
+    .map_err(|status| {
+        let detail = format!("{} returned status code: {}", what, status);
+        match status {
+            404 => PluginError::NotFound(detail).into(),
+            status => PluginError::HttpStatus(status, detail).into(),
+        }
+    })
+}
 
-    let exchange_rate = rates[&to_currency]
-        .as_f64()
-        .ok_or_else(|| anyhow::anyhow!("Exchange rate not found for {} to {}", from_currency, to_currency))?;
+/// Return the first successful `send`, or an error listing every URL tried
+/// with its failure: `RateLimited` when the last one was still answering 429,
+/// otherwise `Network`.
+fn first_success<T>(urls: &[String], mut send: impl FnMut(&str) -> Result<T, HttpError>) -> Result<T> {
+    let mut failures = Vec::new();
+    let mut rate_limited = false;
+    for url in urls {
+        match send(url) {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                rate_limited = matches!(e, HttpError::Status { status: 429, .. });
+                failures.push(format!("{}: {}", url, e));
+            }
+        }
+    }
+    let detail = format!("All {} API endpoints failed ({})", urls.len(), failures.join("; "));
+    Err(if rate_limited { PluginError::RateLimited(detail) } else { PluginError::Network(detail) }.into())
+}
+
+/// Connect timeout for upstream requests: `EXCHANGE_RATE_TIMEOUT_SECS` when set
+/// to a whole number of seconds, otherwise `TIMEOUT_SECS`.
+fn request_timeout() -> Duration {
+    Duration::from_secs(parse_timeout(std::env::var(TIMEOUT_VAR).ok().as_deref()))
+}
+
+fn parse_timeout(value: Option<&str>) -> u64 {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(TIMEOUT_SECS)
+}
+
+/// The currencies endpoint for `version` on every provider, in the order they
+/// are tried, each followed by `suffix` (e.g. `/usd.json`).
+fn endpoint_urls(version: &str, suffix: &str) -> Vec<String> {
+    [primary_endpoint(version), fallback_endpoint(version), github_endpoint(version)]
+        .into_iter()
+        .map(|endpoint| endpoint + suffix)
+        .collect()
+}
 
-    let converted_amount = amount * exchange_rate;
+fn primary_endpoint(version: &str) -> String {
+    format!("https://cdn.jsdelivr.net/npm/@fawazahmed0/currency-api@{}/v1/currencies", version)
+}
+
+/// Cloudflare Pages copy of the currency-api dataset at `version`.
+fn fallback_endpoint(version: &str) -> String {
+    format!("https://{}.currency-api.pages.dev/v1/currencies", version)
+}
+
+/// GitHub raw copy of the dataset, with `version` as the git ref. It doesn't
+/// depend on npm, so it stays up when both CDN copies are down.
+fn github_endpoint(version: &str) -> String {
+    format!("https://raw.githubusercontent.com/fawazahmed0/exchange-api/{}/v1/currencies", version)
+}
+
+/// Convert `amount` using an already-fetched rate table for `from_currency`.
+///
+/// Identical currencies always convert at 1.0 without consulting the table.
+fn compute_conversion(
+    rates: &Map<String, Value>,
+    from_currency: &str,
+    to_currency: &str,
+    amount: f64,
+    last_updated: &str,
+) -> Result<ConversionResponse> {
+    let exchange_rate = if from_currency == to_currency {
+        1.0
+    } else {
+        rates
+            .get(to_currency)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| PluginError::NotFound(format!("Exchange rate not found for {} to {}", from_currency, to_currency)))?
+    };
 
     Ok(ConversionResponse {
-        from_currency,
-        to_currency,
+        from_currency: from_currency.to_string(),
+        to_currency: to_currency.to_string(),
         amount,
-        converted_amount,
+        converted_amount: amount * exchange_rate,
+        formatted_amount: None,
         exchange_rate,
-        last_updated,
+        inverse_rate: inverse_rate(exchange_rate),
+        last_updated: last_updated.to_string(),
+        last_updated_ts: date_timestamp(last_updated),
+        pivot_currency: None,
+        route: None,
+        direct_rate: None,
+        round_trip_amount: None,
+        via: None,
     })
 }
 
-fn list_currencies_internal() -> Result<CurrencyListResponse> {
-    let request_url = format!("{}.json", PRIMARY_ENDPOINT);
-
-    let response = Client::new()
-        .get(&request_url)
-        .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-        .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-        .send()
-        .or_else(|_| {
-            let fallback_url = format!("{}.json", FALLBACK_ENDPOINT);
-            Client::new()
-                .get(&fallback_url)
-                .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-                .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-                .send()
-        })
-        .context("Both primary and fallback API requests failed")?;
+/// Midnight UTC of an upstream `YYYY-MM-DD` date, or `None` for placeholders
+/// like "unknown" and "N/A".
+fn date_timestamp(date: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .map(|day| day.and_time(chrono::NaiveTime::MIN).and_utc())
+}
 
-    let status = response.status_code();
-    if !(200..300).contains(&status) {
-        anyhow::bail!("Currencies API returned status code: {}", status);
-    }
+/// `1 / rate`, or `None` when the rate is zero and has no inverse.
+fn inverse_rate(exchange_rate: f64) -> Option<f64> {
+    (exchange_rate != 0.0).then(|| 1.0 / exchange_rate)
+}
+
+/// Convert `from -> pivot -> to` by chaining the two table rates, keeping the
+/// direct `from -> to` quote (when the table has one) for comparison.
+fn compute_pivot_conversion(
+    from_rates: &Map<String, Value>,
+    pivot_rates: &Map<String, Value>,
+    from_currency: &str,
+    pivot_currency: &str,
+    to_currency: &str,
+    amount: f64,
+    last_updated: &str,
+) -> Result<ConversionResponse> {
+    let to_pivot = compute_conversion(from_rates, from_currency, pivot_currency, 1.0, last_updated)?.exchange_rate;
+    let from_pivot = compute_conversion(pivot_rates, pivot_currency, to_currency, 1.0, last_updated)?.exchange_rate;
+    let exchange_rate = to_pivot * from_pivot;
+
+    Ok(ConversionResponse {
+        from_currency: from_currency.to_string(),
+        to_currency: to_currency.to_string(),
+        amount,
+        converted_amount: amount * exchange_rate,
+        formatted_amount: None,
+        exchange_rate,
+        inverse_rate: inverse_rate(exchange_rate),
+        last_updated: last_updated.to_string(),
+        last_updated_ts: date_timestamp(last_updated),
+        pivot_currency: Some(pivot_currency.to_string()),
+        route: Some("pivot".to_string()),
+        direct_rate: from_rates.get(to_currency).and_then(Value::as_f64),
+        round_trip_amount: None,
+        via: None,
+    })
+}
+
+/// Convert `from -> via -> to` for a pair with no direct rate. Unlike a requested
+/// pivot, there is no direct quote to compare against, so only `via` is set.
+fn compute_via_conversion(
+    from_rates: &Map<String, Value>,
+    via_rates: &Map<String, Value>,
+    from_currency: &str,
+    via_currency: &str,
+    to_currency: &str,
+    amount: f64,
+    last_updated: &str,
+) -> Result<ConversionResponse> {
+    let mut conversion =
+        compute_pivot_conversion(from_rates, via_rates, from_currency, via_currency, to_currency, amount, last_updated)?;
+    conversion.pivot_currency = None;
+    conversion.route = None;
+    conversion.via = Some(via_currency.to_string());
+    Ok(conversion)
+}
+
+/// Snapshot the latest rate table for `base_currency` for later offline conversion.
+fn export_snapshot_internal(base_currency: String) -> Result<RateSnapshot> {
+    let base_currency = base_currency.trim().to_lowercase();
+
+    let exchange_data = fetch_rate_table(&base_currency, LATEST)?;
+    let (date, rates) = schema::rate_table(&exchange_data, &base_currency)?;
+
+    Ok(RateSnapshot::new(
+        &base_currency,
+        date.unwrap_or("unknown"),
+        &Utc::now().to_rfc3339(),
+        "currency-api",
+        rates,
+    ))
+}
+
+/// Convert from a snapshot's base currency using only the snapshot; no network access.
+fn convert_offline_internal(snapshot_json: String, to_currency: String, amount: f64) -> Result<ConversionResponse> {
+    let snapshot = RateSnapshot::parse(&snapshot_json)?;
+    let to_currency = to_currency.trim().to_lowercase();
 
-    let body_bytes = response.body()
-        .context("Failed to read response body")?;
+    compute_conversion(
+        &snapshot.rate_table(),
+        &snapshot.base_currency,
+        &to_currency,
+        amount,
+        &snapshot.date,
+    )
+}
+
+fn list_currencies_internal() -> Result<CurrencyListResponse> {
+    let body_bytes = fetch_with_fallbacks(&endpoint_urls(LATEST, ".json"), "Currencies API")?;
 
     let body = String::from_utf8(body_bytes)
         .context("Invalid UTF-8 in response")?;
@@ -200,7 +906,7 @@ fn list_currencies_internal() -> Result<CurrencyListResponse> {
 
     let currencies_obj = currencies_data
         .as_object()
-        .ok_or_else(|| anyhow::anyhow!("Invalid currencies response format"))?;
+        .ok_or_else(|| PluginError::Parse("Invalid currencies response format".to_string()))?;
 
     let mut currencies = HashMap::new();
     for (code, name_value) in currencies_obj {
@@ -212,29 +918,243 @@ fn list_currencies_internal() -> Result<CurrencyListResponse> {
     Ok(CurrencyListResponse { currencies })
 }
 
+fn currencies_by_region_internal() -> Result<CurrencyRegionsResponse> {
+    let CurrencyListResponse { currencies } = list_currencies_internal()?;
+    Ok(group_by_region(currencies))
+}
+
+/// Bucket a code-to-name map into display regions, skipping empty ones.
+fn group_by_region(currencies: HashMap<String, String>) -> CurrencyRegionsResponse {
+    let mut regions: Vec<RegionGroup> = regions::REGION_ORDER
+        .iter()
+        .map(|region| RegionGroup {
+            region: region.to_string(),
+            currencies: Default::default(),
+        })
+        .collect();
+
+    for (code, name) in currencies {
+        let region = regions::region_for(&code);
+        if let Some(group) = regions.iter_mut().find(|g| g.region == region) {
+            group.currencies.insert(code, name);
+        }
+    }
+
+    regions.retain(|group| !group.currencies.is_empty());
+    CurrencyRegionsResponse { regions }
+}
+
+/// The currency API is public, so only optional tuning variables are read.
+fn required_config() -> Vec<ConfigVar> {
+    vec![
+        ConfigVar {
+            name: rate_cache::TTL_VAR.to_string(),
+            required: false,
+            description: format!(
+                "Seconds to reuse a fetched rate table (default {}, 0 disables caching)",
+                rate_cache::DEFAULT_TTL_SECS
+            ),
+        },
+        ConfigVar {
+            name: TIMEOUT_VAR.to_string(),
+            required: false,
+            description: format!("Connect timeout in seconds for currency API requests (default {})", TIMEOUT_SECS),
+        },
+        ConfigVar {
+            name: retry::MAX_ATTEMPTS_VAR.to_string(),
+            required: false,
+            description: format!(
+                "Attempts per endpoint for transient failures, with jittered exponential backoff (default {})",
+                retry::DEFAULT_MAX_ATTEMPTS
+            ),
+        },
+        ConfigVar {
+            name: plugin_http::debug::DEBUG_VAR.to_string(),
+            required: false,
+            description: "Set to 1 to log requests, responses, retries and errors to stderr".to_string(),
+        },
+    ]
+}
+
 struct ExchangeRateComponent;
 
 impl Guest for ExchangeRateComponent {
-    fn get_exchange_rates(base_currency: String, target_currencies: String) -> Result<String, String> {
-        match get_exchange_rates_internal(base_currency, target_currencies) {
+    fn get_exchange_rates(
+        base_currency: String,
+        target_currencies: String,
+        majors_only: bool,
+        with_metrics: bool,
+        include_names: bool,
+    ) -> Result<String, String> {
+        match get_exchange_rates_internal(base_currency, parse_targets(&target_currencies), majors_only, with_metrics, include_names) {
+            Ok(rates) => {
+                serde_json::to_string(&rates)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Exchange rate request failed"))
+            }
+        }
+    }
+
+    fn get_exchange_rates_list(base_currency: String, targets: Vec<String>) -> Result<String, String> {
+        match get_exchange_rates_internal(base_currency, targets, false, false, false) {
             Ok(rates) => {
                 serde_json::to_string(&rates)
-                    .map_err(|e| format!("Failed to serialize results: {}", e))
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
             }
             Err(e) => {
-                Err(format!("Exchange rate request failed: {}", e))
+                Err(error::to_wit(e, "Exchange rate request failed"))
             }
         }
     }
 
-    fn convert_currency(from_currency: String, to_currency: String, amount: f64) -> Result<String, String> {
-        match convert_currency_internal(from_currency, to_currency, amount) {
+    fn get_exchange_rates_detailed(base_currency: String, target_currencies: String) -> Result<String, String> {
+        match get_exchange_rates_detailed_internal(base_currency, target_currencies) {
+            Ok(rates) => {
+                serde_json::to_string(&rates)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Exchange rate request failed"))
+            }
+        }
+    }
+
+    fn get_pair_rate(base_currency: String, target_currency: String) -> Result<String, String> {
+        match get_pair_rate_internal(base_currency, target_currency) {
+            Ok(pair) => {
+                serde_json::to_string(&pair)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Pair rate request failed"))
+            }
+        }
+    }
+
+    fn rate_change(base_currency: String, target_currency: String, start_date: String, end_date: String) -> Result<String, String> {
+        match rate_change_internal(base_currency, target_currency, start_date, end_date) {
+            Ok(change) => {
+                serde_json::to_string(&change)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Rate change request failed"))
+            }
+        }
+    }
+
+    fn get_historical_rates(base_currency: String, target_currencies: String, date: String) -> Result<String, String> {
+        match get_historical_rates_internal(base_currency, target_currencies, date) {
+            Ok(rates) => {
+                serde_json::to_string(&rates)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize results: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Historical rate request failed"))
+            }
+        }
+    }
+
+    fn convert_currency(from_currency: String, to_currency: String, amount: f64, options: ConvertOptions) -> Result<String, String> {
+        let pivot_currency = options.pivot_currency.unwrap_or_default();
+        let result = convert_currency_internal(from_currency, to_currency, amount, pivot_currency)
+            .and_then(|conversion| if options.round_trip { with_round_trip(conversion) } else { Ok(conversion) })
+            .map(|conversion| with_decimal_places(conversion, options.decimal_places))
+            .map(|conversion| with_formatted_amount(conversion, options.include_formatted));
+        match result {
+            Ok(conversion) => {
+                serde_json::to_string(&conversion)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Currency conversion failed"))
+            }
+        }
+    }
+
+    fn convert_currency_str(from_currency: String, to_currency: String, amount: String) -> Result<String, String> {
+        let amount = amount::parse_amount(&amount).map_err(|e| error::report(PluginError::from(e)))?;
+        match convert_currency_internal(from_currency, to_currency, amount, String::new()) {
             Ok(conversion) => {
                 serde_json::to_string(&conversion)
-                    .map_err(|e| format!("Failed to serialize result: {}", e))
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Currency conversion failed"))
+            }
+        }
+    }
+
+    fn convert_currency_batch(from_currency: String, conversions: Vec<(String, f64)>) -> Result<String, String> {
+        match convert_currency_batch_internal(from_currency, conversions) {
+            Ok(batch) => {
+                serde_json::to_string(&batch)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Batch conversion failed"))
+            }
+        }
+    }
+
+    fn generate_quote(from_currency: String, to_currency: String, amount: f64) -> Result<String, String> {
+        match generate_quote_internal(from_currency, to_currency, amount) {
+            Ok(quote) => {
+                serde_json::to_string(&quote)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize quote: {}", e))))
             }
             Err(e) => {
-                Err(format!("Currency conversion failed: {}", e))
+                Err(error::to_wit(e, "Quote failed"))
+            }
+        }
+    }
+
+    fn convert_currency_avg(from_currency: String, to_currency: String, amount: f64, days: u32) -> Result<String, String> {
+        match convert_currency_avg_internal(from_currency, to_currency, amount, days) {
+            Ok(conversion) => {
+                serde_json::to_string(&conversion)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Currency conversion failed"))
+            }
+        }
+    }
+
+    fn convert_and_split(from_currency: String, to_currency: String, amount: f64, ways: u32) -> Result<String, String> {
+        match convert_and_split_internal(from_currency, to_currency, amount, ways) {
+            Ok(split) => {
+                serde_json::to_string(&split)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Currency split failed"))
+            }
+        }
+    }
+
+    fn export_snapshot(base_currency: String) -> Result<String, String> {
+        match export_snapshot_internal(base_currency) {
+            Ok(snapshot) => {
+                serde_json::to_string(&snapshot)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize snapshot: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Snapshot failed"))
+            }
+        }
+    }
+
+    fn convert_offline(snapshot_json: String, to_currency: String, amount: f64) -> Result<String, String> {
+        match convert_offline_internal(snapshot_json, to_currency, amount) {
+            Ok(conversion) => {
+                serde_json::to_string(&conversion)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize result: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Offline conversion failed"))
             }
         }
     }
@@ -243,13 +1163,564 @@ impl Guest for ExchangeRateComponent {
         match list_currencies_internal() {
             Ok(currencies) => {
                 serde_json::to_string(&currencies)
-                    .map_err(|e| format!("Failed to serialize currencies: {}", e))
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize currencies: {}", e))))
+            }
+            Err(e) => {
+                Err(error::to_wit(e, "Failed to list currencies"))
+            }
+        }
+    }
+
+    fn currencies_by_region() -> Result<String, String> {
+        match currencies_by_region_internal() {
+            Ok(grouped) => {
+                serde_json::to_string(&grouped)
+                    .map_err(|e| error::report(PluginError::Parse(format!("Failed to serialize currencies: {}", e))))
             }
             Err(e) => {
-                Err(format!("Failed to list currencies: {}", e))
+                Err(error::to_wit(e, "Failed to group currencies"))
             }
         }
     }
+
+    fn required_config() -> Vec<ConfigVar> {
+        required_config()
+    }
 }
 
-export!(ExchangeRateComponent);
\ No newline at end of file
+export!(ExchangeRateComponent with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn usd_rates() -> Map<String, Value> {
+        json!({
+            "eur": 0.9234,
+            "jpy": 149.5,
+            "btc": 0.0000158,
+            "gbp": "not-a-number"
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn converts_using_table_rate() {
+        let conversion = compute_conversion(&usd_rates(), "usd", "eur", 100.0, "2025-09-23").unwrap();
+        assert_eq!(conversion.from_currency, "usd");
+        assert_eq!(conversion.to_currency, "eur");
+        assert_eq!(conversion.amount, 100.0);
+        assert_eq!(conversion.exchange_rate, 0.9234);
+        assert!((conversion.converted_amount - 92.34).abs() < 1e-9);
+        assert_eq!(conversion.last_updated, "2025-09-23");
+        assert_eq!(
+            conversion.last_updated_ts.map(|ts| ts.to_rfc3339()).as_deref(),
+            Some("2025-09-23T00:00:00+00:00")
+        );
+    }
+
+    #[test]
+    fn placeholder_dates_have_no_timestamp() {
+        assert_eq!(date_timestamp("unknown"), None);
+        assert_eq!(date_timestamp("N/A"), None);
+        assert_eq!(date_timestamp("2025-13-01"), None);
+        let json = serde_json::to_value(compute_conversion(&usd_rates(), "usd", "eur", 1.0, "unknown").unwrap()).unwrap();
+        let key = if cfg!(feature = "camel-case") { "lastUpdatedTs" } else { "last_updated_ts" };
+        assert_eq!(json[key], Value::Null);
+        let json = serde_json::to_value(compute_conversion(&usd_rates(), "usd", "eur", 1.0, "2025-09-23").unwrap()).unwrap();
+        assert_eq!(json[key], "2025-09-23T00:00:00Z");
+    }
+
+    #[test]
+    fn same_currency_converts_at_parity() {
+        let conversion = compute_conversion(&Map::new(), "usd", "usd", 42.5, "N/A").unwrap();
+        assert_eq!(conversion.exchange_rate, 1.0);
+        assert_eq!(conversion.converted_amount, 42.5);
+        assert_eq!(conversion.last_updated, "N/A");
+    }
+
+    #[test]
+    fn split_shares_sum_to_total() {
+        assert_eq!(split_minor_units(10000, 3), vec![3334, 3333, 3333]);
+        assert_eq!(split_minor_units(10001, 4), vec![2501, 2500, 2500, 2500]);
+        assert_eq!(split_minor_units(2, 5), vec![1, 1, 0, 0, 0]);
+        assert_eq!(split_minor_units(9234, 1), vec![9234]);
+
+        let shares = split_minor_units(123457, 7);
+        assert_eq!(shares.iter().sum::<u64>(), 123457);
+        assert!(shares.iter().max().unwrap() - shares.iter().min().unwrap() <= 1);
+    }
+
+    #[test]
+    fn split_rejects_zero_ways_and_bad_amounts() {
+        let err = convert_and_split_internal("usd".into(), "usd".into(), 10.0, 0).unwrap_err();
+        assert!(err.to_string().contains("ways must be at least 1"));
+        assert!(convert_and_split_internal("usd".into(), "usd".into(), -5.0, 2).is_err());
+        assert!(convert_and_split_internal("usd".into(), "usd".into(), f64::NAN, 2).is_err());
+    }
+
+    #[test]
+    fn splits_same_currency_without_fetching() {
+        let split = convert_and_split_internal("usd".into(), "USD".into(), 100.0, 3).unwrap();
+        assert_eq!(split.converted_amount, 100.0);
+        assert_eq!(split.shares, vec![33.34, 33.33, 33.33]);
+    }
+
+    #[test]
+    fn selects_targets_and_majors() {
+        let rates = usd_rates();
+
+        let all = select_rates(&rates, &[], false);
+        assert_eq!(all.len(), 3);
+
+        let targets = select_rates(&rates, &parse_targets(" EUR, btc,xxx"), false);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets["btc"], 0.0000158);
+
+        let majors = select_rates(&rates, &[], true);
+        assert_eq!(majors.len(), 2);
+        assert!(majors.contains_key("eur") && majors.contains_key("jpy"));
+
+        let major_targets = select_rates(&rates, &parse_targets("eur,btc"), true);
+        assert_eq!(major_targets.keys().collect::<Vec<_>>(), vec!["eur"]);
+    }
+
+    #[test]
+    fn labels_crypto_and_fiat_rates() {
+        let response = ExchangeRateResponse {
+            base_currency: "usd".to_string(),
+            rates: select_rates(&usd_rates(), &[], false),
+            names: None,
+            last_updated: "2025-09-23".to_string(),
+            last_updated_ts: date_timestamp("2025-09-23"),
+            unknown_currencies: vec!["xxx".to_string()],
+            elapsed_ms: None,
+        };
+
+        let detailed = label_rates(response);
+        assert_eq!(detailed.rates.keys().collect::<Vec<_>>(), vec!["btc", "eur", "jpy"]);
+        assert!(detailed.rates["btc"].is_crypto);
+        assert_eq!(detailed.rates["btc"].rate, 0.0000158);
+        assert!(!detailed.rates["eur"].is_crypto);
+        assert!(!detailed.rates["jpy"].is_crypto);
+        assert_eq!(detailed.unknown_currencies, vec!["xxx"]);
+    }
+
+    #[test]
+    fn keeps_names_for_returned_rates_only() {
+        let rates = select_rates(&usd_rates(), &parse_targets("eur,jpy"), false);
+        let currencies: HashMap<String, String> = [("eur", "Euro"), ("jpy", "Japanese Yen"), ("usd", "US Dollar")]
+            .into_iter()
+            .map(|(code, name)| (code.to_string(), name.to_string()))
+            .collect();
+
+        let names = names_for(&rates, currencies);
+        assert_eq!(names.len(), 2);
+        assert_eq!(names["eur"], "Euro");
+        assert!(!names.contains_key("usd"));
+    }
+
+    #[test]
+    fn normalizes_target_lists() {
+        let targets = vec![" EUR ".to_string(), "".to_string(), "Jpy".to_string(), "  ".to_string()];
+        assert_eq!(normalize_targets(targets), vec!["eur", "jpy"]);
+        assert_eq!(parse_targets(" EUR,,jpy "), vec!["eur", "jpy"]);
+    }
+
+    #[test]
+    fn reports_unknown_targets() {
+        let rates = usd_rates();
+        assert_eq!(unknown_targets(&rates, &parse_targets("eur, XXX,gbp,xxx,jpy")), vec!["xxx", "gbp"]);
+        assert!(unknown_targets(&rates, &[]).is_empty());
+        assert!(unknown_targets(&rates, &parse_targets("eur,btc")).is_empty());
+    }
+
+    #[test]
+    fn pivots_through_intermediate_table() {
+        let eur_rates = json!({"jpy": 162.0, "usd": 1.083}).as_object().unwrap().clone();
+        let conversion =
+            compute_pivot_conversion(&usd_rates(), &eur_rates, "usd", "eur", "jpy", 10.0, "2025-09-23").unwrap();
+
+        assert!((conversion.exchange_rate - 0.9234 * 162.0).abs() < 1e-9);
+        assert!((conversion.converted_amount - 10.0 * 0.9234 * 162.0).abs() < 1e-9);
+        assert_eq!(conversion.pivot_currency.as_deref(), Some("eur"));
+        assert_eq!(conversion.route.as_deref(), Some("pivot"));
+        assert_eq!(conversion.direct_rate, Some(149.5));
+    }
+
+    #[test]
+    fn pivot_requires_both_legs() {
+        let eur_rates = json!({"usd": 1.083}).as_object().unwrap().clone();
+        let err = compute_pivot_conversion(&usd_rates(), &eur_rates, "usd", "eur", "jpy", 1.0, "2025-09-23").unwrap_err();
+        assert_eq!(err.to_string(), "not_found: Exchange rate not found for eur to jpy");
+
+        let err = compute_pivot_conversion(&usd_rates(), &eur_rates, "usd", "chf", "jpy", 1.0, "2025-09-23").unwrap_err();
+        assert_eq!(err.to_string(), "not_found: Exchange rate not found for usd to chf");
+    }
+
+    #[test]
+    fn hops_through_usd_for_missing_pairs() {
+        let xcd_rates = json!({"usd": 0.37, "eur": 0.34}).as_object().unwrap().clone();
+        let usd_rates = json!({"xpf": 110.0}).as_object().unwrap().clone();
+
+        let conversion = compute_via_conversion(&xcd_rates, &usd_rates, "xcd", "usd", "xpf", 10.0, "2025-09-23").unwrap();
+        assert!((conversion.exchange_rate - 0.37 * 110.0).abs() < 1e-9);
+        assert!((conversion.converted_amount - 407.0).abs() < 1e-9);
+        assert_eq!(conversion.via.as_deref(), Some("usd"));
+        assert!(conversion.pivot_currency.is_none() && conversion.route.is_none() && conversion.direct_rate.is_none());
+
+        let err = compute_via_conversion(&xcd_rates, &usd_rates, "xcd", "usd", "xxx", 1.0, "2025-09-23").unwrap_err();
+        assert_eq!(err.to_string(), "not_found: Exchange rate not found for usd to xxx");
+    }
+
+    #[test]
+    fn direct_conversions_omit_pivot_fields() {
+        let conversion = compute_conversion(&usd_rates(), "usd", "eur", 1.0, "2025-09-23").unwrap();
+        let json = serde_json::to_value(&conversion).unwrap();
+        assert!(json.get("pivot_currency").is_none() && json.get("route").is_none());
+    }
+
+    #[test]
+    fn converts_offline_from_snapshot() {
+        let snapshot = RateSnapshot::new("usd", "2025-09-23", "2025-09-23T08:00:00+00:00", "currency-api", &usd_rates());
+        let json = serde_json::to_string(&snapshot).unwrap();
+
+        let conversion = convert_offline_internal(json.clone(), " EUR ".into(), 100.0).unwrap();
+        assert_eq!(conversion.from_currency, "usd");
+        assert!((conversion.converted_amount - 92.34).abs() < 1e-9);
+        assert_eq!(conversion.last_updated, "2025-09-23");
+
+        let err = convert_offline_internal(json, "gbp".into(), 1.0).unwrap_err();
+        assert_eq!(err.to_string(), "not_found: Exchange rate not found for usd to gbp");
+    }
+
+    #[test]
+    fn batch_fails_only_missing_lines() {
+        let lines = vec![
+            ("eur".to_string(), 10.0),
+            ("usd".to_string(), 5.0),
+            ("xxx".to_string(), 1.0),
+            ("jpy".to_string(), 2.0),
+        ];
+        let items = convert_lines(&usd_rates(), "usd", lines, "2025-09-23");
+        assert_eq!(items.len(), 4);
+
+        let BatchConversionItem::Converted(eur) = &items[0] else { panic!("eur should convert") };
+        assert!((eur.converted_amount - 9.234).abs() < 1e-9);
+        let BatchConversionItem::Converted(same) = &items[1] else { panic!("usd should convert") };
+        assert_eq!(same.exchange_rate, 1.0);
+        let BatchConversionItem::Failed(missing) = &items[2] else { panic!("xxx should fail") };
+        assert_eq!(missing.error, "not_found: Exchange rate not found for usd to xxx");
+        assert!(matches!(items[3], BatchConversionItem::Converted(_)));
+
+        let json = serde_json::to_value(&items).unwrap();
+        assert_eq!(json[2]["error"], "not_found: Exchange rate not found for usd to xxx");
+        assert!(json[0].get("error").is_none());
+    }
+
+    #[test]
+    fn totals_batches_with_a_single_target() {
+        let lines = |targets: &[&str]| targets.iter().map(|to| (to.to_string(), 10.0)).collect::<Vec<_>>();
+
+        let same = convert_lines(&usd_rates(), "usd", lines(&["eur", "eur"]), "2025-09-23");
+        assert!((batch_total(&same).unwrap() - 18.468).abs() < 1e-9);
+
+        let mixed = convert_lines(&usd_rates(), "usd", lines(&["eur", "jpy"]), "2025-09-23");
+        assert_eq!(batch_total(&mixed), None);
+
+        let failed = convert_lines(&Map::new(), "usd", lines(&["eur", "eur"]), "2025-09-23");
+        assert_eq!(batch_total(&failed), None);
+
+        assert_eq!(batch_total(&[]), None);
+    }
+
+    #[test]
+    fn includes_inverse_rate() {
+        let conversion = compute_conversion(&usd_rates(), "usd", "jpy", 1.0, "2025-09-23").unwrap();
+        assert!((conversion.inverse_rate.unwrap() - 1.0 / 149.5).abs() < 1e-12);
+
+        let same = compute_conversion(&Map::new(), "usd", "usd", 1.0, "N/A").unwrap();
+        assert_eq!(same.inverse_rate, Some(1.0));
+
+        assert_eq!(inverse_rate(0.0), None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "camel-case"))]
+    fn serializes_missing_inverse_as_null() {
+        let zero = json!({"xyz": 0.0}).as_object().unwrap().clone();
+        let json = serde_json::to_value(compute_conversion(&zero, "usd", "xyz", 1.0, "N/A").unwrap()).unwrap();
+        assert!(json.as_object().unwrap().contains_key("inverse_rate"));
+        assert!(json["inverse_rate"].is_null());
+    }
+
+    #[test]
+    fn reports_optional_config() {
+        let config = required_config();
+        let names: Vec<&str> = config.iter().map(|var| var.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "EXCHANGE_RATE_CACHE_TTL_SECS",
+                "EXCHANGE_RATE_TIMEOUT_SECS",
+                "EXCHANGE_RATE_MAX_RETRIES",
+                "NOORLE_DEBUG"
+            ]
+        );
+        assert!(config.iter().all(|var| !var.required));
+    }
+
+    #[test]
+    fn parses_timeout_override() {
+        assert_eq!(parse_timeout(None), TIMEOUT_SECS);
+        assert_eq!(parse_timeout(Some("5")), 5);
+        assert_eq!(parse_timeout(Some(" 120 ")), 120);
+        assert_eq!(parse_timeout(Some("0")), TIMEOUT_SECS);
+        assert_eq!(parse_timeout(Some("2.5")), TIMEOUT_SECS);
+        assert_eq!(parse_timeout(Some("fast")), TIMEOUT_SECS);
+    }
+
+    #[test]
+    fn tries_every_provider_in_order() {
+        let urls = endpoint_urls("2024-06-01", "/usd.json");
+        assert_eq!(urls.len(), 3);
+        assert!(urls[0].starts_with("https://cdn.jsdelivr.net/"));
+        assert!(urls[1].starts_with("https://2024-06-01.currency-api.pages.dev/"));
+        assert!(urls[2].starts_with("https://raw.githubusercontent.com/"));
+        assert!(urls.iter().all(|url| url.ends_with("/v1/currencies/usd.json")));
+
+        let mut tried = Vec::new();
+        let result = first_success(&urls, |url| {
+            tried.push(url.to_string());
+            if tried.len() < 3 { Err(status_error(503, 3)) } else { Ok(url.len()) }
+        });
+        assert_eq!(result.unwrap(), urls[2].len());
+        assert_eq!(tried, urls);
+    }
+
+    fn status_error(status: u16, attempts: u32) -> HttpError {
+        HttpError::Status { status, body: Vec::new(), attempts }
+    }
+
+    #[test]
+    fn lists_every_endpoint_when_all_fail() {
+        let urls = vec!["https://a.example/usd.json".to_string(), "https://b.example/usd.json".to_string()];
+        let err = first_success::<()>(&urls, |url| {
+            Err(HttpError::Transport { message: format!("down ({})", &url[8..9]), attempts: 1 })
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "network: All 2 API endpoints failed (https://a.example/usd.json: down (a); https://b.example/usd.json: down (b))"
+        );
+    }
+
+    #[test]
+    fn reports_a_final_429_as_rate_limited() {
+        let urls = vec!["https://a.example/usd.json".to_string(), "https://b.example/usd.json".to_string()];
+        let err = first_success::<()>(&urls, |url| {
+            Err(if url.contains("//a.") { status_error(503, 3) } else { status_error(429, 3) })
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "rate_limited: All 2 API endpoints failed (https://a.example/usd.json: HTTP status 503 after 3 attempts; \
+             https://b.example/usd.json: HTTP status 429 after 3 attempts)"
+        );
+
+        let err = first_success::<()>(&urls, |url| {
+            Err(if url.contains("//a.") { status_error(429, 3) } else { status_error(503, 3) })
+        })
+        .unwrap_err();
+        assert_eq!(PluginError::from(err).kind(), "network");
+    }
+
+    #[test]
+    fn rounds_half_up_on_decimal_digits() {
+        assert_eq!(round_half_up(1234.5678901, 2), 1234.57);
+        assert_eq!(round_half_up(1.005, 2), 1.01);
+        assert_eq!(round_half_up(2.5, 0), 3.0);
+        assert_eq!(round_half_up(9.995, 2), 10.0);
+        assert_eq!(round_half_up(-1.005, 2), -1.01);
+        assert_eq!(round_half_up(0.0000158, 6), 0.000016);
+        assert_eq!(round_half_up(12.3, 4), 12.3);
+        assert_eq!(round_half_up(9_007_199_254_740_993.0, 2), 9_007_199_254_740_993.0);
+        assert_eq!(round_half_up(1e21, 10), 1e21);
+        assert!(round_half_up(f64::NAN, 2).is_nan());
+    }
+
+    #[test]
+    fn applies_decimal_places_to_converted_amount_only() {
+        let conversion = compute_conversion(&usd_rates(), "usd", "eur", 1336.9861, "2025-09-23").unwrap();
+        let raw = conversion.converted_amount;
+
+        let rounded = with_decimal_places(conversion, Some(2));
+        assert_eq!(rounded.converted_amount, 1234.57);
+        assert_eq!(rounded.exchange_rate, 0.9234);
+
+        let clamped = with_decimal_places(rounded, Some(50));
+        assert_eq!(clamped.converted_amount, 1234.57);
+
+        let untouched = compute_conversion(&usd_rates(), "usd", "eur", 1336.9861, "2025-09-23").unwrap();
+        assert_eq!(with_decimal_places(untouched, None).converted_amount, raw);
+    }
+
+    #[test]
+    fn round_trips_at_the_reverse_table_rate() {
+        let conversion = compute_conversion(&usd_rates(), "usd", "eur", 100.0, "2025-09-23").unwrap();
+        let eur_rates = json!({"usd": 1.0825}).as_object().unwrap().clone();
+
+        let round_trip = apply_round_trip(conversion, &eur_rates).unwrap();
+        // 92.34 EUR back at the EUR table's own rate, not 1 / 0.9234.
+        assert!((round_trip.round_trip_amount.unwrap() - 99.95805).abs() < 1e-9);
+        assert_eq!(with_decimal_places(round_trip, Some(2)).round_trip_amount, Some(99.96));
+
+        let same = compute_conversion(&Map::new(), "usd", "usd", 5.0, "N/A").unwrap();
+        assert_eq!(apply_round_trip(same, &Map::new()).unwrap().round_trip_amount, Some(5.0));
+
+        let conversion = compute_conversion(&usd_rates(), "usd", "eur", 1.0, "2025-09-23").unwrap();
+        assert!(apply_round_trip(conversion, &Map::new()).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_the_table_only_for_a_missing_pair() {
+        let table = || Ok((Some("2025-09-23".to_string()), 0.92));
+
+        let pair = Ok(json!({"date": "2025-09-24", "eur": 0.93}));
+        assert_eq!(pair_or_table_rate(pair, "eur", table).unwrap(), (Some("2025-09-24".to_string()), 0.93));
+
+        let missing = Err(PluginError::NotFound("Exchange rate API returned status code: 404".to_string()).into());
+        assert_eq!(pair_or_table_rate(missing, "eur", table).unwrap(), (Some("2025-09-23".to_string()), 0.92));
+
+        let failures: [anyhow::Error; 3] = [
+            PluginError::RateLimited("All 3 API endpoints failed (HTTP status 429 after 3 attempts)".to_string()).into(),
+            PluginError::HttpStatus(403, "Exchange rate API returned status code: 403".to_string()).into(),
+            serde_json::from_str::<Value>("{").context("Failed to parse JSON response").unwrap_err(),
+        ];
+        for failure in failures {
+            let expected = failure.to_string();
+            let err = pair_or_table_rate(Err(failure), "eur", || panic!("table fetched after a non-404 failure")).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn missing_pair_is_an_error() {
+        let err = compute_conversion(&usd_rates(), "usd", "xxx", 1.0, "2025-09-23").unwrap_err();
+        assert_eq!(err.to_string(), "not_found: Exchange rate not found for usd to xxx");
+    }
+
+    #[test]
+    fn non_numeric_rate_is_an_error() {
+        assert!(compute_conversion(&usd_rates(), "usd", "gbp", 1.0, "2025-09-23").is_err());
+    }
+
+    #[cfg(feature = "camel-case")]
+    #[test]
+    fn serializes_camel_case_keys() {
+        let conversion = compute_conversion(&usd_rates(), "usd", "eur", 1.0, "2025-09-23").unwrap();
+        let json = serde_json::to_value(&conversion).unwrap();
+        assert!(json.get("convertedAmount").is_some());
+        assert!(json.get("converted_amount").is_none());
+    }
+
+    #[test]
+    fn lists_recent_dates_newest_first() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        assert_eq!(recent_dates(today, 3), vec!["2024-03-02", "2024-03-01", "2024-02-29"]);
+        assert_eq!(recent_dates(today, 1), vec!["2024-03-02"]);
+    }
+
+    #[test]
+    fn validates_history_dates() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        assert_eq!(validate_history_date("2023-01-15", today).unwrap(), "2023-01-15");
+        assert_eq!(validate_history_date("2024-03-02", today).unwrap(), "2024-03-02");
+
+        for malformed in ["2023-1-15", "15/01/2023", "2023-02-30", "latest", ""] {
+            let err = validate_history_date(malformed, today).unwrap_err();
+            assert!(err.to_string().contains("expected YYYY-MM-DD"), "{}", malformed);
+        }
+
+        let err = validate_history_date("2024-03-03", today).unwrap_err();
+        assert_eq!(err.to_string(), "Date 2024-03-03 is in the future; the latest available is 2024-03-02");
+    }
+
+    #[test]
+    fn computes_rate_change() {
+        let (absolute, percent) = change_between(0.90, 0.9234);
+        assert!((absolute - 0.0234).abs() < 1e-12);
+        assert!((percent.unwrap() - 2.6).abs() < 1e-9);
+
+        let (absolute, percent) = change_between(1.25, 1.0);
+        assert_eq!(absolute, -0.25);
+        assert_eq!(percent, Some(-20.0));
+
+        assert_eq!(change_between(0.0, 1.0), (1.0, None));
+    }
+
+    #[test]
+    fn averages_available_samples() {
+        assert_eq!(mean(&[0.9, 0.92, 0.94]).map(|m| (m * 100.0).round()), Some(92.0));
+        assert_eq!(mean(&[]), None);
+    }
+
+    #[test]
+    fn skips_missing_days_but_keeps_other_failure_kinds() {
+        let dates = recent_dates(NaiveDate::from_ymd_opt(2025, 9, 24).unwrap(), 3);
+        let missing = || anyhow::Error::from(PluginError::NotFound("Exchange rate API returned status code: 404".to_string()));
+        let limited = || anyhow::Error::from(PluginError::RateLimited("All 3 API endpoints failed".to_string()));
+
+        let samples = collect_samples(&dates, |date| match date {
+            "2025-09-24" => Err(missing()),
+            "2025-09-23" => Ok(Some(0.92)),
+            _ => Err(limited()),
+        })
+        .unwrap();
+        assert_eq!(samples, vec![("2025-09-23".to_string(), 0.92)]);
+
+        assert!(collect_samples(&dates, |_| Err(missing())).unwrap().is_empty());
+        assert!(collect_samples(&dates, |_| Ok(None)).unwrap().is_empty());
+
+        let err = collect_samples(&dates, |date| if date == "2025-09-24" { Err(limited()) } else { Err(missing()) }).unwrap_err();
+        assert_eq!(PluginError::from(err).kind(), "rate_limited");
+
+        let schema = || schema::rate_table(&json!({"date": "2025-09-24"}), "usd").map(|_| None).map_err(anyhow::Error::from);
+        let err = collect_samples(&dates, |_| schema()).unwrap_err();
+        assert_eq!(PluginError::from(err).kind(), "parse");
+    }
+
+    #[test]
+    fn groups_currencies_in_render_order() {
+        let currencies = HashMap::from([
+            ("usd".to_string(), "US Dollar".to_string()),
+            ("eur".to_string(), "Euro".to_string()),
+            ("btc".to_string(), "Bitcoin".to_string()),
+            ("xau".to_string(), "Gold".to_string()),
+            ("cad".to_string(), "Canadian Dollar".to_string()),
+        ]);
+
+        let grouped = group_by_region(currencies);
+        let names: Vec<&str> = grouped.regions.iter().map(|g| g.region.as_str()).collect();
+        assert_eq!(names, vec!["Americas", "Europe", "Crypto", "Other"]);
+
+        let americas: Vec<&String> = grouped.regions[0].currencies.keys().collect();
+        assert_eq!(americas, vec!["cad", "usd"]);
+        assert_eq!(grouped.regions[3].currencies["xau"], "Gold");
+    }
+
+    #[test]
+    fn preserves_precision_at_extremes() {
+        let tiny = compute_conversion(&usd_rates(), "usd", "btc", 0.01, "2025-09-23").unwrap();
+        assert!((tiny.converted_amount - 0.000000158).abs() < 1e-18);
+
+        let large = compute_conversion(&usd_rates(), "usd", "jpy", 1_000_000_000.0, "2025-09-23").unwrap();
+        assert_eq!(large.converted_amount, 149_500_000_000.0);
+
+        let zero = compute_conversion(&usd_rates(), "usd", "eur", 0.0, "2025-09-23").unwrap();
+        assert_eq!(zero.converted_amount, 0.0);
+    }
+}
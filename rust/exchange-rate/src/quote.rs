@@ -0,0 +1,111 @@
+use crate::types::ConversionResponse;
+use sha2::{Digest, Sha256};
+
+/// Hex characters kept from the SHA-256 digest for a quote id.
+const QUOTE_ID_CHARS: usize = 16;
+/// Decimals shown for figures of 1 or more; smaller ones get enough to keep
+/// about five significant digits.
+const FIGURE_DECIMALS: usize = 4;
+const MAX_FIGURE_DECIMALS: usize = 12;
+
+/// One-line summary of a conversion, e.g.
+/// "100 USD = 92.34 EUR (rate 0.9234, as of 2024-06-01)".
+pub fn format_quote(conversion: &ConversionResponse) -> String {
+    format!(
+        "{} {} = {} {} (rate {}, as of {})",
+        format_figure(conversion.amount),
+        conversion.from_currency.to_uppercase(),
+        format_figure(conversion.converted_amount),
+        conversion.to_currency.to_uppercase(),
+        format_figure(conversion.exchange_rate),
+        conversion.last_updated
+    )
+}
+
+/// Stable id for a quote: the same pair, amount and rate date always give the same id.
+pub fn quote_id(conversion: &ConversionResponse) -> String {
+    let key = format!(
+        "{}|{}|{}|{}",
+        conversion.from_currency, conversion.to_currency, conversion.amount, conversion.last_updated
+    );
+    let digest = Sha256::digest(key.as_bytes());
+    digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>()[..QUOTE_ID_CHARS]
+        .to_string()
+}
+
+/// Format a number without trailing zeros, keeping small values (e.g. crypto rates) readable.
+fn format_figure(value: f64) -> String {
+    let magnitude = value.abs();
+    let decimals = if magnitude >= 1.0 || magnitude == 0.0 || !magnitude.is_finite() {
+        FIGURE_DECIMALS
+    } else {
+        ((-magnitude.log10().floor()) as usize + FIGURE_DECIMALS).min(MAX_FIGURE_DECIMALS)
+    };
+
+    let formatted = format!("{:.*}", decimals, value);
+    if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversion(to: &str, amount: f64, rate: f64) -> ConversionResponse {
+        ConversionResponse {
+            from_currency: "usd".to_string(),
+            to_currency: to.to_string(),
+            amount,
+            converted_amount: amount * rate,
+            formatted_amount: None,
+            exchange_rate: rate,
+            inverse_rate: Some(1.0 / rate),
+            last_updated: "2024-06-01".to_string(),
+            last_updated_ts: None,
+            pivot_currency: None,
+            route: None,
+            direct_rate: None,
+            round_trip_amount: None,
+            via: None,
+        }
+    }
+
+    #[test]
+    fn formats_a_readable_quote() {
+        assert_eq!(
+            format_quote(&conversion("eur", 100.0, 0.9234)),
+            "100 USD = 92.34 EUR (rate 0.9234, as of 2024-06-01)"
+        );
+        assert_eq!(
+            format_quote(&conversion("btc", 100.0, 0.0000158)),
+            "100 USD = 0.00158 BTC (rate 0.0000158, as of 2024-06-01)"
+        );
+    }
+
+    #[test]
+    fn trims_figures() {
+        assert_eq!(format_figure(149.5), "149.5");
+        assert_eq!(format_figure(1234.56789), "1234.5679");
+        assert_eq!(format_figure(0.0), "0");
+        assert_eq!(format_figure(-2.5), "-2.5");
+    }
+
+    #[test]
+    fn quote_id_is_stable_per_inputs_and_date() {
+        let id = quote_id(&conversion("eur", 100.0, 0.9234));
+        assert_eq!(id.len(), QUOTE_ID_CHARS);
+        assert_eq!(id, quote_id(&conversion("eur", 100.0, 0.9234)));
+        assert_ne!(id, quote_id(&conversion("eur", 101.0, 0.9234)));
+        assert_ne!(id, quote_id(&conversion("gbp", 100.0, 0.9234)));
+
+        let mut later = conversion("eur", 100.0, 0.9234);
+        later.last_updated = "2024-06-02".to_string();
+        assert_ne!(id, quote_id(&later));
+    }
+}
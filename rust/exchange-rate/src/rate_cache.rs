@@ -0,0 +1,155 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Environment variable overriding how long a fetched rate table is reused.
+pub const TTL_VAR: &str = "EXCHANGE_RATE_CACHE_TTL_SECS";
+pub const DEFAULT_TTL_SECS: u64 = 300;
+
+struct Entry {
+    fetched_at: Instant,
+    table: Value,
+}
+
+/// Parsed rate tables keyed by `(base_currency, version)`, where version is
+/// `latest` or a `YYYY-MM-DD` snapshot date.
+#[derive(Default)]
+pub struct RateCache {
+    entries: HashMap<(String, String), Entry>,
+}
+
+impl RateCache {
+    /// Return the cached table if it is younger than `ttl`, otherwise call
+    /// `fetch` and remember its result. Failed fetches are not cached.
+    pub fn get_or_fetch(
+        &mut self,
+        base_currency: &str,
+        version: &str,
+        ttl: Duration,
+        now: Instant,
+        fetch: impl FnOnce() -> Result<Value>,
+    ) -> Result<Value> {
+        let key = (base_currency.to_string(), version.to_string());
+        if let Some(entry) = self.entries.get(&key) {
+            if now.saturating_duration_since(entry.fetched_at) < ttl {
+                return Ok(entry.table.clone());
+            }
+        }
+
+        let table = fetch()?;
+        self.entries
+            .retain(|_, entry| now.saturating_duration_since(entry.fetched_at) < ttl);
+        if !ttl.is_zero() {
+            self.entries.insert(key, Entry { fetched_at: now, table: table.clone() });
+        }
+        Ok(table)
+    }
+}
+
+// A component instance runs single-threaded, so a thread-local lives exactly as
+// long as the instance and needs no locking. Each new instance starts empty.
+thread_local! {
+    static CACHE: RefCell<RateCache> = RefCell::default();
+}
+
+/// Fetch through the instance-wide cache.
+pub fn cached(base_currency: &str, version: &str, fetch: impl FnOnce() -> Result<Value>) -> Result<Value> {
+    CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .get_or_fetch(base_currency, version, ttl(), Instant::now(), fetch)
+    })
+}
+
+/// TTL from the environment, falling back to the default when unset or invalid.
+/// `0` disables caching.
+fn ttl() -> Duration {
+    Duration::from_secs(parse_ttl(std::env::var(TTL_VAR).ok().as_deref()))
+}
+
+fn parse_ttl(value: Option<&str>) -> u64 {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_TTL_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::Cell;
+
+    const TTL: Duration = Duration::from_secs(DEFAULT_TTL_SECS);
+
+    #[test]
+    fn reuses_tables_within_ttl() {
+        let mut cache = RateCache::default();
+        let requests = Cell::new(0);
+        let fetch = || {
+            requests.set(requests.get() + 1);
+            Ok(json!({"date": "2025-09-23", "usd": {"eur": 0.92}}))
+        };
+        let start = Instant::now();
+
+        let first = cache.get_or_fetch("usd", "latest", TTL, start, fetch).unwrap();
+        let second = cache
+            .get_or_fetch("usd", "latest", TTL, start + Duration::from_secs(299), fetch)
+            .unwrap();
+        assert_eq!(requests.get(), 1);
+        assert_eq!(first, second);
+
+        cache
+            .get_or_fetch("usd", "latest", TTL, start + Duration::from_secs(300), fetch)
+            .unwrap();
+        assert_eq!(requests.get(), 2);
+    }
+
+    #[test]
+    fn keys_by_base_and_date() {
+        let mut cache = RateCache::default();
+        let requests = Cell::new(0);
+        let fetch = || {
+            requests.set(requests.get() + 1);
+            Ok(json!({}))
+        };
+        let now = Instant::now();
+
+        cache.get_or_fetch("usd", "latest", TTL, now, fetch).unwrap();
+        cache.get_or_fetch("eur", "latest", TTL, now, fetch).unwrap();
+        cache.get_or_fetch("usd", "2024-06-01", TTL, now, fetch).unwrap();
+        cache.get_or_fetch("usd", "2024-06-01", TTL, now, fetch).unwrap();
+        assert_eq!(requests.get(), 3);
+    }
+
+    #[test]
+    fn does_not_cache_failures_or_zero_ttl() {
+        let mut cache = RateCache::default();
+        let now = Instant::now();
+
+        assert!(cache
+            .get_or_fetch("usd", "latest", TTL, now, || Err(anyhow::anyhow!("offline")))
+            .is_err());
+        let table = cache.get_or_fetch("usd", "latest", TTL, now, || Ok(json!({"ok": true}))).unwrap();
+        assert_eq!(table, json!({"ok": true}));
+
+        let requests = Cell::new(0);
+        let fetch = || {
+            requests.set(requests.get() + 1);
+            Ok(json!({}))
+        };
+        cache.get_or_fetch("gbp", "latest", Duration::ZERO, now, fetch).unwrap();
+        cache.get_or_fetch("gbp", "latest", Duration::ZERO, now, fetch).unwrap();
+        assert_eq!(requests.get(), 2);
+    }
+
+    #[test]
+    fn parses_ttl_override() {
+        assert_eq!(parse_ttl(None), DEFAULT_TTL_SECS);
+        assert_eq!(parse_ttl(Some(" 60 ")), 60);
+        assert_eq!(parse_ttl(Some("0")), 0);
+        assert_eq!(parse_ttl(Some("five minutes")), DEFAULT_TTL_SECS);
+        assert_eq!(parse_ttl(Some("-1")), DEFAULT_TTL_SECS);
+    }
+}
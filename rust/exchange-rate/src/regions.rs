@@ -0,0 +1,114 @@
+/// Region buckets in the order they should be rendered.
+pub const REGION_ORDER: &[&str] = &["Americas", "Europe", "Asia", "Africa", "Oceania", "Crypto", "Other"];
+
+const AMERICAS: &[&str] = &[
+    "usd", "cad", "mxn", "brl", "ars", "clp", "cop", "pen", "uyu", "pyg", "bob", "ves", "vef",
+    "gtq", "hnl", "nio", "crc", "pab", "dop", "cup", "cuc", "jmd", "ttd", "bbd", "bsd", "bzd",
+    "xcd", "htg", "awg", "ang", "srd", "gyd", "kyd", "bmd", "fkp",
+];
+
+const EUROPE: &[&str] = &[
+    "eur", "gbp", "chf", "nok", "sek", "dkk", "isk", "pln", "czk", "huf", "ron", "bgn", "hrk",
+    "rsd", "bam", "mkd", "all", "mdl", "uah", "byn", "rub", "try", "gip", "jep", "ggp", "imp",
+];
+
+const ASIA: &[&str] = &[
+    "jpy", "cny", "hkd", "mop", "twd", "krw", "kpw", "inr", "pkr", "bdt", "lkr", "npr", "btn",
+    "mvr", "afn", "irr", "iqd", "sar", "aed", "qar", "kwd", "bhd", "omr", "yer", "jod", "ils",
+    "lbp", "syp", "kzt", "uzs", "kgs", "tjs", "tmt", "mnt", "thb", "vnd", "khr", "lak", "mmk",
+    "myr", "sgd", "idr", "php", "bnd", "amd", "azn", "gel",
+];
+
+const AFRICA: &[&str] = &[
+    "zar", "ngn", "egp", "kes", "ghs", "mad", "dzd", "tnd", "lyd", "etb", "ugx", "tzs", "rwf",
+    "bif", "xof", "xaf", "mur", "scr", "mga", "mwk", "zmw", "zwl", "bwp", "nad", "lsl", "szl",
+    "aoa", "mzn", "cdf", "gmd", "gnf", "sll", "sle", "lrd", "cve", "std", "stn", "djf", "ern",
+    "sos", "sdg", "ssp", "kmf", "mro", "mru", "shp",
+];
+
+const OCEANIA: &[&str] = &["aud", "nzd", "fjd", "pgk", "sbd", "top", "vuv", "wst", "xpf"];
+
+/// Cryptocurrency tickers present in the currency-api dataset.
+pub const CRYPTO: &[&str] = &[
+    "btc", "eth", "ltc", "xrp", "bch", "ada", "doge", "dot", "sol", "bnb", "usdt", "usdc",
+    "busd", "dai", "xlm", "trx", "link", "matic", "pol", "avax", "shib", "etc", "xmr", "eos",
+    "atom", "uni", "algo", "xtz", "fil", "icp", "near", "apt", "arb", "op", "ton", "sui",
+    "hbar", "vet", "egld", "aave", "mkr", "sand", "mana", "axs", "ape", "crv", "ftm", "grt",
+    "ldo", "qnt", "rune", "snx", "theta", "zec", "dash", "neo", "waves", "xem", "bsv", "bat",
+    "chz", "comp", "enj", "gala", "imx", "inj", "kava", "ksm", "leo", "lrc", "okb", "pepe",
+    "stx", "tusd", "wbtc", "xdc", "1inch", "cake", "cro", "flow", "gt", "ht", "kcs", "mina",
+    "nexo", "rpl", "sei", "trump", "wld",
+];
+
+/// The most-traded currencies, in order of share of global FX turnover
+/// (top 20 of the BIS Triennial Central Bank Survey, 2022).
+pub const MAJORS: &[&str] = &[
+    "usd", "eur", "jpy", "gbp", "cny", "aud", "cad", "chf", "hkd", "sgd", "sek", "krw", "nok",
+    "nzd", "inr", "mxn", "twd", "zar", "brl", "dkk",
+];
+
+/// Whether a lowercase currency code is a known cryptocurrency ticker.
+pub fn is_crypto(code: &str) -> bool {
+    CRYPTO.contains(&code)
+}
+
+/// Look up the display region for a lowercase currency code.
+///
+/// Codes without a bundled mapping land in "Other".
+pub fn region_for(code: &str) -> &'static str {
+    let buckets: [(&'static str, &[&str]); 6] = [
+        ("Americas", AMERICAS),
+        ("Europe", EUROPE),
+        ("Asia", ASIA),
+        ("Africa", AFRICA),
+        ("Oceania", OCEANIA),
+        ("Crypto", CRYPTO),
+    ];
+
+    buckets
+        .iter()
+        .find(|(_, codes)| codes.contains(&code))
+        .map(|(region, _)| *region)
+        .unwrap_or("Other")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_known_codes_to_regions() {
+        assert_eq!(region_for("usd"), "Americas");
+        assert_eq!(region_for("eur"), "Europe");
+        assert_eq!(region_for("jpy"), "Asia");
+        assert_eq!(region_for("zar"), "Africa");
+        assert_eq!(region_for("nzd"), "Oceania");
+        assert_eq!(region_for("btc"), "Crypto");
+    }
+
+    #[test]
+    fn classifies_crypto_tickers() {
+        assert!(is_crypto("btc"));
+        assert!(is_crypto("usdt"));
+        assert!(!is_crypto("usd"));
+        assert!(!is_crypto("xau"));
+    }
+
+    #[test]
+    fn unmapped_codes_fall_into_other() {
+        assert_eq!(region_for("xau"), "Other");
+        assert_eq!(region_for(""), "Other");
+    }
+
+    #[test]
+    fn no_code_belongs_to_two_regions() {
+        let all = [AMERICAS, EUROPE, ASIA, AFRICA, OCEANIA, CRYPTO];
+        for (i, codes) in all.iter().enumerate() {
+            for code in codes.iter() {
+                for other in all.iter().skip(i + 1) {
+                    assert!(!other.contains(code), "{} is mapped twice", code);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+/// Environment variable overriding how many times each endpoint is tried.
+pub const MAX_ATTEMPTS_VAR: &str = "EXCHANGE_RATE_MAX_RETRIES";
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// Upper bound on the override, so a typo can't stall a call for minutes.
+const MAX_ATTEMPTS_LIMIT: u32 = 10;
+
+/// Attempts per endpoint from the environment, falling back to the default when
+/// unset or invalid.
+pub fn max_attempts() -> u32 {
+    parse_max_attempts(std::env::var(MAX_ATTEMPTS_VAR).ok().as_deref())
+}
+
+fn parse_max_attempts(value: Option<&str>) -> u32 {
+    value
+        .and_then(|value| value.trim().parse::<u32>().ok())
+        .map(|attempts| attempts.clamp(1, MAX_ATTEMPTS_LIMIT))
+        .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_max_attempts_override() {
+        assert_eq!(parse_max_attempts(None), DEFAULT_MAX_ATTEMPTS);
+        assert_eq!(parse_max_attempts(Some("5")), 5);
+        assert_eq!(parse_max_attempts(Some("0")), 1);
+        assert_eq!(parse_max_attempts(Some("1000")), MAX_ATTEMPTS_LIMIT);
+        assert_eq!(parse_max_attempts(Some("lots")), DEFAULT_MAX_ATTEMPTS);
+    }
+}
@@ -0,0 +1,167 @@
+use serde_json::{Map, Value};
+use std::fmt;
+
+const SNIPPET_MAX_CHARS: usize = 200;
+
+/// The currency-api payload no longer has the shape this plugin understands.
+///
+/// Carries a redacted outline of what was received (top-level keys and value
+/// types, never the values themselves) so upstream format changes are easy to spot.
+#[derive(Debug)]
+pub struct UnexpectedSchema {
+    pub reason: String,
+    pub received: String,
+}
+
+impl fmt::Display for UnexpectedSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UnexpectedSchema: {} (received: {})", self.reason, self.received)
+    }
+}
+
+impl std::error::Error for UnexpectedSchema {}
+
+/// Validate a rate-table payload and return `(date, rates)`.
+///
+/// Expected shape: `{"date": "YYYY-MM-DD", "<base>": {"<code>": <rate>, ...}}`.
+/// A missing `date` is tolerated and reported as `None`.
+pub fn rate_table<'a>(
+    data: &'a Value,
+    base_currency: &str,
+) -> Result<(Option<&'a str>, &'a Map<String, Value>), UnexpectedSchema> {
+    let root = data.as_object().ok_or_else(|| unexpected(data, "expected a JSON object"))?;
+
+    let date = match root.get("date") {
+        None => None,
+        Some(Value::String(date)) => Some(date.as_str()),
+        Some(_) => return Err(unexpected(data, "`date` is not a string")),
+    };
+
+    let rates = match root.get(base_currency) {
+        Some(Value::Object(rates)) => rates,
+        Some(_) => return Err(unexpected(data, &format!("`{}` is not an object of rates", base_currency))),
+        None => return Err(unexpected(data, &format!("missing `{}` rate table", base_currency))),
+    };
+
+    Ok((date, rates))
+}
+
+/// Validate a single-pair payload and return `(date, rate)`.
+///
+/// Expected shape: `{"date": "YYYY-MM-DD", "<target>": <rate>}`.
+pub fn pair_rate<'a>(data: &'a Value, target_currency: &str) -> Result<(Option<&'a str>, f64), UnexpectedSchema> {
+    let root = data.as_object().ok_or_else(|| unexpected(data, "expected a JSON object"))?;
+
+    let date = match root.get("date") {
+        None => None,
+        Some(Value::String(date)) => Some(date.as_str()),
+        Some(_) => return Err(unexpected(data, "`date` is not a string")),
+    };
+
+    let rate = match root.get(target_currency) {
+        Some(Value::Number(rate)) => rate.as_f64().unwrap_or_default(),
+        Some(_) => return Err(unexpected(data, &format!("`{}` is not a number", target_currency))),
+        None => return Err(unexpected(data, &format!("missing `{}` rate", target_currency))),
+    };
+
+    Ok((date, rate))
+}
+
+fn unexpected(data: &Value, reason: &str) -> UnexpectedSchema {
+    UnexpectedSchema {
+        reason: reason.to_string(),
+        received: outline(data),
+    }
+}
+
+/// Describe a value by its top-level keys and their types, truncated.
+fn outline(data: &Value) -> String {
+    let described = match data {
+        Value::Object(map) => {
+            let fields: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, type_name(value)))
+                .collect();
+            format!("{{{}}}", fields.join(", "))
+        }
+        other => type_name(other),
+    };
+
+    if described.chars().count() > SNIPPET_MAX_CHARS {
+        let truncated: String = described.chars().take(SNIPPET_MAX_CHARS).collect();
+        format!("{}...", truncated)
+    } else {
+        described
+    }
+}
+
+fn type_name(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Array(items) => format!("array({})", items.len()),
+        Value::Object(map) => format!("object({} keys)", map.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn accepts_expected_shape() {
+        let data = json!({"date": "2025-09-23", "usd": {"eur": 0.92}});
+        let (date, rates) = rate_table(&data, "usd").unwrap();
+        assert_eq!(date, Some("2025-09-23"));
+        assert_eq!(rates["eur"], 0.92);
+    }
+
+    #[test]
+    fn tolerates_missing_date() {
+        let data = json!({"usd": {"eur": 0.92}});
+        assert_eq!(rate_table(&data, "usd").unwrap().0, None);
+    }
+
+    #[test]
+    fn reports_renamed_rate_table_without_leaking_values() {
+        let data = json!({"date": "2025-09-23", "base": "usd", "rates": {"eur": 0.92, "gbp": 0.79}});
+        let err = rate_table(&data, "usd").unwrap_err();
+        assert_eq!(err.reason, "missing `usd` rate table");
+        assert_eq!(err.received, "{base: string, date: string, rates: object(2 keys)}");
+        assert!(!err.to_string().contains("0.92"));
+    }
+
+    #[test]
+    fn reports_non_object_payloads() {
+        let err = rate_table(&json!([1, 2, 3]), "usd").unwrap_err();
+        assert_eq!(err.to_string(), "UnexpectedSchema: expected a JSON object (received: array(3))");
+
+        let err = rate_table(&json!({"date": 20250923, "usd": {}}), "usd").unwrap_err();
+        assert_eq!(err.reason, "`date` is not a string");
+    }
+
+    #[test]
+    fn parses_single_pair_payloads() {
+        let data = json!({"date": "2025-09-23", "eur": 0.9234});
+        assert_eq!(pair_rate(&data, "eur").unwrap(), (Some("2025-09-23"), 0.9234));
+
+        let err = pair_rate(&json!({"date": "2025-09-23", "eur": {"usd": 1.08}}), "eur").unwrap_err();
+        assert_eq!(err.reason, "`eur` is not a number");
+        let err = pair_rate(&data, "gbp").unwrap_err();
+        assert_eq!(err.reason, "missing `gbp` rate");
+    }
+
+    #[test]
+    fn truncates_long_outlines() {
+        let mut map = Map::new();
+        for i in 0..100 {
+            map.insert(format!("key{:03}", i), json!(i));
+        }
+        let err = rate_table(&Value::Object(map), "usd").unwrap_err();
+        assert!(err.received.ends_with("..."));
+        assert_eq!(err.received.chars().count(), SNIPPET_MAX_CHARS + 3);
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Marker identifying a snapshot produced by this plugin.
+pub const SNAPSHOT_FORMAT: &str = "noorle-exchange-rate-snapshot";
+/// Bump when the snapshot layout changes incompatibly.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A self-contained copy of one base currency's rate table for offline use.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct RateSnapshot {
+    pub format: String,
+    pub version: u32,
+    pub base_currency: String,
+    /// Date of the upstream rate table ("unknown" if it wasn't reported).
+    pub date: String,
+    /// When the snapshot was taken (RFC 3339).
+    pub created_at: String,
+    pub source: String,
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl RateSnapshot {
+    pub fn new(base_currency: &str, date: &str, created_at: &str, source: &str, rates: &Map<String, Value>) -> Self {
+        RateSnapshot {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            base_currency: base_currency.to_string(),
+            date: date.to_string(),
+            created_at: created_at.to_string(),
+            source: source.to_string(),
+            rates: rates
+                .iter()
+                .filter_map(|(code, rate)| rate.as_f64().map(|rate| (code.clone(), rate)))
+                .collect(),
+        }
+    }
+
+    /// Parse and sanity-check a snapshot produced by `export-snapshot`.
+    pub fn parse(json: &str) -> Result<Self> {
+        let snapshot: RateSnapshot = serde_json::from_str(json).context("Invalid snapshot: not a rate snapshot")?;
+
+        if snapshot.format != SNAPSHOT_FORMAT {
+            anyhow::bail!("Invalid snapshot: unknown format '{}'", snapshot.format);
+        }
+        if snapshot.version != SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "Invalid snapshot: unsupported version {} (expected {})",
+                snapshot.version,
+                SNAPSHOT_VERSION
+            );
+        }
+        if snapshot.base_currency.trim().is_empty() {
+            anyhow::bail!("Invalid snapshot: missing base currency");
+        }
+        if snapshot.base_currency != snapshot.base_currency.to_lowercase() {
+            anyhow::bail!("Invalid snapshot: base currency must be lowercase");
+        }
+        if snapshot.rates.is_empty() {
+            anyhow::bail!("Invalid snapshot: no rates");
+        }
+        // A table quotes its own base at parity; anything else means the rates
+        // belong to a different base than the snapshot claims.
+        if let Some(self_rate) = snapshot.rates.get(&snapshot.base_currency) {
+            if (self_rate - 1.0).abs() > 1e-9 {
+                anyhow::bail!(
+                    "Invalid snapshot: rates are not based on {} ({} to {} is {})",
+                    snapshot.base_currency,
+                    snapshot.base_currency,
+                    snapshot.base_currency,
+                    self_rate
+                );
+            }
+        }
+        if let Some((code, rate)) = snapshot.rates.iter().find(|(_, rate)| !rate.is_finite() || **rate <= 0.0) {
+            anyhow::bail!("Invalid snapshot: bad rate {} for {}", rate, code);
+        }
+
+        Ok(snapshot)
+    }
+
+    /// The rates in the shape `compute_conversion` expects.
+    pub fn rate_table(&self) -> Map<String, Value> {
+        self.rates
+            .iter()
+            .map(|(code, rate)| (code.clone(), Value::from(*rate)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn usd_snapshot() -> RateSnapshot {
+        let rates = json!({"usd": 1.0, "eur": 0.9234, "jpy": 149.5, "bad": "n/a"});
+        RateSnapshot::new("usd", "2025-09-23", "2025-09-23T08:00:00+00:00", "currency-api", rates.as_object().unwrap())
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let snapshot = usd_snapshot();
+        assert_eq!(snapshot.rates.len(), 3);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(RateSnapshot::parse(&json).unwrap(), snapshot);
+        assert_eq!(snapshot.rate_table()["eur"], 0.9234);
+    }
+
+    #[test]
+    fn rejects_foreign_or_corrupt_snapshots() {
+        assert!(RateSnapshot::parse("{}").is_err());
+        assert!(RateSnapshot::parse("not json").is_err());
+
+        let mut snapshot = usd_snapshot();
+        snapshot.version = 2;
+        let err = RateSnapshot::parse(&serde_json::to_string(&snapshot).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("unsupported version 2"));
+
+        let mut snapshot = usd_snapshot();
+        snapshot.rates.clear();
+        assert!(RateSnapshot::parse(&serde_json::to_string(&snapshot).unwrap()).is_err());
+    }
+
+    #[test]
+    fn rejects_base_that_does_not_match_rates() {
+        let mut snapshot = usd_snapshot();
+        snapshot.base_currency = "eur".to_string();
+        snapshot.rates.insert("eur".to_string(), 0.9234);
+        let err = RateSnapshot::parse(&serde_json::to_string(&snapshot).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("rates are not based on eur"));
+    }
+}
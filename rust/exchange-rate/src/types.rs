@@ -1,24 +1,194 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct ExchangeRateResponse {
     pub base_currency: String,
     pub rates: HashMap<String, f64>,
+    /// Full names for the returned rate codes, only when names were requested;
+    /// empty if the currency list could not be fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub names: Option<HashMap<String, String>>,
     pub last_updated: String,
+    /// `last_updated` as a timestamp (midnight UTC of that date); null when the
+    /// date is unknown.
+    pub last_updated_ts: Option<DateTime<Utc>>,
+    /// Requested targets that have no rate in the table, so callers can warn about them.
+    pub unknown_currencies: Vec<String>,
+    /// Time taken to fetch the rate table, only when metrics were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u32>,
 }
 
+/// One rate in a `get-exchange-rates-detailed` table.
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct DetailedRate {
+    pub rate: f64,
+    /// Whether the code is a known cryptocurrency ticker rather than a fiat currency.
+    pub is_crypto: bool,
+}
+
+/// Exchange rates with each entry labeled as crypto or fiat.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct DetailedExchangeRateResponse {
+    pub base_currency: String,
+    pub rates: BTreeMap<String, DetailedRate>,
+    pub last_updated: String,
+    pub last_updated_ts: Option<DateTime<Utc>>,
+    pub unknown_currencies: Vec<String>,
+}
+
+/// The rate for a single currency pair.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct PairRateResponse {
+    pub base: String,
+    pub target: String,
+    pub rate: f64,
+    pub last_updated: String,
+}
+
+/// Movement of a pair's rate between two dates.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct RateChangeResponse {
+    pub base: String,
+    pub target: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub start_rate: f64,
+    pub end_rate: f64,
+    pub absolute_change: f64,
+    /// `(end - start) / start * 100`; null when the start rate is 0.
+    pub percent_change: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct ConversionResponse {
     pub from_currency: String,
     pub to_currency: String,
     pub amount: f64,
     pub converted_amount: f64,
+    /// `converted_amount` for display, e.g. "€1,234.56", when requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted_amount: Option<String>,
     pub exchange_rate: f64,
+    /// `1 / exchange_rate`, the `to -> from` rate; null when the rate is zero.
+    pub inverse_rate: Option<f64>,
     pub last_updated: String,
+    /// `last_updated` as a timestamp (midnight UTC of that date); null when the
+    /// date is unknown.
+    pub last_updated_ts: Option<DateTime<Utc>>,
+    /// Currency the conversion was routed through, when a pivot was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pivot_currency: Option<String>,
+    /// "pivot" or "direct": which rate `exchange_rate` is, when a pivot was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// The direct quote for comparison, when routed through a pivot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direct_rate: Option<f64>,
+    /// `converted_amount` converted back at the `to -> from` rate from the target's
+    /// own table, when a round trip was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round_trip_amount: Option<f64>,
+    /// Intermediary currency used because the table had no direct rate for the pair.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+}
+
+/// One line of a batch conversion: the conversion, or why that line failed.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BatchConversionItem {
+    Converted(ConversionResponse),
+    Failed(ConversionError),
+}
+
+impl BatchConversionItem {
+    pub fn to_currency(&self) -> &str {
+        match self {
+            BatchConversionItem::Converted(conversion) => &conversion.to_currency,
+            BatchConversionItem::Failed(failure) => &failure.to_currency,
+        }
+    }
+}
+
+/// Every line of a batch conversion, plus the cart total when one can be given.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct BatchConversionResponse {
+    pub items: Vec<BatchConversionItem>,
+    /// Sum of `converted_amount` when every line converted into the same currency;
+    /// null for mixed targets or when any line failed.
+    pub batch_total: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ConversionError {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub amount: f64,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct AverageConversionResponse {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub amount: f64,
+    pub converted_amount: f64,
+    pub average_rate: f64,
+    pub days_requested: u32,
+    pub days_available: u32,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct CurrencyListResponse {
     pub currencies: HashMap<String, String>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct RegionGroup {
+    pub region: String,
+    pub currencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct CurrencyRegionsResponse {
+    pub regions: Vec<RegionGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct SplitResponse {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub amount: f64,
+    pub exchange_rate: f64,
+    pub converted_amount: f64,
+    pub ways: u32,
+    pub shares: Vec<f64>,
+    pub last_updated: String,
+}
+
+/// A conversion plus a shareable one-line quote.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct QuoteResponse {
+    pub conversion: ConversionResponse,
+    pub quote: String,
+    pub quote_id: String,
+}
@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Label given to articles that share no significant term with any other.
+pub const OTHER_LABEL: &str = "Other";
+/// Terms shorter than this are too generic to group on.
+const MIN_TERM_CHARS: usize = 3;
+/// Most terms shown in a cluster label.
+const MAX_LABEL_TERMS: usize = 3;
+
+const STOPWORDS: &[&str] = &[
+    "about", "after", "against", "all", "also", "amid", "and", "are", "before", "being",
+    "between", "but", "can", "could", "did", "does", "down", "for", "from", "had", "has", "have",
+    "her", "his", "how", "into", "its", "just", "more", "most", "new", "not", "now", "off", "one",
+    "only", "our", "out", "over", "says", "said", "she", "than", "that", "the", "their", "them",
+    "then", "there", "these", "they", "this", "those", "through", "under", "was", "were", "what",
+    "when", "where", "which", "while", "who", "why", "will", "with", "would", "you", "your",
+];
+
+/// A group of articles sharing significant terms, identified by index.
+#[derive(Debug, PartialEq)]
+pub struct Cluster {
+    pub label: String,
+    /// Dominant terms, most widely shared first.
+    pub terms: Vec<String>,
+    /// Indexes into the clustered articles, in their original order.
+    pub members: Vec<usize>,
+}
+
+/// Lowercased significant terms of `text`, skipping stopwords, numbers and short words.
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TERM_CHARS)
+        .filter(|word| !word.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+}
+
+/// Group articles by overlapping title/description terms.
+///
+/// Each input is an article's `(title, description)`. Terms from the query are ignored, since
+/// every result shares them. Clusters are formed greedily: the term shared by the most
+/// still-unassigned articles seeds a cluster of every unassigned article containing it, until
+/// no term is shared by two articles. Leftovers go into a final "Other" cluster.
+pub fn group(query: &str, articles: &[(Option<&str>, Option<&str>)]) -> Vec<Cluster> {
+    let query_terms: BTreeSet<String> = terms(query).collect();
+    let article_terms: Vec<BTreeSet<String>> = articles
+        .iter()
+        .map(|(title, description)| {
+            terms(title.unwrap_or_default())
+                .chain(terms(description.unwrap_or_default()))
+                .filter(|term| !query_terms.contains(term))
+                .collect()
+        })
+        .collect();
+
+    let mut unassigned: Vec<usize> = (0..articles.len()).collect();
+    let mut clusters = Vec::new();
+
+    loop {
+        let counts = term_counts(&article_terms, &unassigned);
+        // Highest count wins; ties go to the alphabetically first term.
+        let seed = counts
+            .iter()
+            .filter(|(_, count)| **count >= 2)
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map(|(term, _)| (*term).clone());
+        let Some(seed) = seed else { break };
+
+        let (members, rest): (Vec<usize>, Vec<usize>) =
+            unassigned.iter().partition(|&&i| article_terms[i].contains(&seed));
+        unassigned = rest;
+
+        let terms = label_terms(&article_terms, &members, &seed);
+        clusters.push(Cluster {
+            label: terms.join(" / "),
+            terms,
+            members,
+        });
+    }
+
+    if !unassigned.is_empty() {
+        clusters.push(Cluster {
+            label: OTHER_LABEL.to_string(),
+            terms: Vec::new(),
+            members: unassigned,
+        });
+    }
+
+    clusters
+}
+
+/// How many of the `members` articles contain each term.
+fn term_counts<'a>(article_terms: &'a [BTreeSet<String>], members: &[usize]) -> BTreeMap<&'a String, usize> {
+    let mut counts = BTreeMap::new();
+    for &i in members {
+        for term in &article_terms[i] {
+            *counts.entry(term).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// The seed followed by other terms shared by at least two articles and half the cluster,
+/// most common first.
+fn label_terms(article_terms: &[BTreeSet<String>], members: &[usize], seed: &str) -> Vec<String> {
+    let mut shared: Vec<(&String, usize)> = term_counts(article_terms, members)
+        .into_iter()
+        .filter(|(term, count)| term.as_str() != seed && *count >= 2 && count * 2 >= members.len())
+        .collect();
+    shared.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    std::iter::once(seed.to_string())
+        .chain(shared.into_iter().map(|(term, _)| term.clone()))
+        .take(MAX_LABEL_TERMS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_significant_terms() {
+        let found: Vec<String> = terms("The EU's new AI Act: 2024 rules for chatbots").collect();
+        assert_eq!(found, ["act", "rules", "chatbots"]);
+    }
+
+    #[test]
+    fn groups_articles_by_shared_terms() {
+        let articles = [
+            (Some("Tesla recalls Cybertruck over pedal defect"), None),
+            (Some("OpenAI unveils new model"), Some("The model beats benchmarks")),
+            (Some("Cybertruck recall expands"), Some("Tesla widens the pedal recall")),
+            (Some("Rival labs respond to OpenAI model launch"), None),
+            (Some("Stock markets rally"), None),
+        ];
+        let clusters = group("technology", &articles);
+
+        assert_eq!(clusters.len(), 3);
+        assert_eq!(clusters[0].members, [0, 2]);
+        assert_eq!(clusters[0].label, "cybertruck / pedal / tesla");
+        assert_eq!(clusters[1].members, [1, 3]);
+        assert_eq!(clusters[1].terms, ["model", "openai"]);
+        assert_eq!(clusters[2].label, OTHER_LABEL);
+        assert_eq!(clusters[2].members, [4]);
+    }
+
+    #[test]
+    fn ignores_query_terms() {
+        let articles = [(Some("Climate summit opens"), None), (Some("Climate protests grow"), None)];
+        let clusters = group("climate", &articles);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].label, OTHER_LABEL);
+    }
+
+    #[test]
+    fn handles_no_articles() {
+        assert!(group("anything", &[]).is_empty());
+    }
+}
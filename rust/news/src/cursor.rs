@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize};
+
+/// Current cursor format version. Bump when the encoded fields change.
+const CURSOR_VERSION: u32 = 1;
+
+/// Position in a paged search, handed to callers as an opaque token.
+///
+/// The token is URL-safe base64 over a small versioned JSON document, so it
+/// carries the query it was issued for along with the next page to fetch.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    #[serde(rename = "v")]
+    pub version: u32,
+    #[serde(rename = "q")]
+    pub query: String,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Cursor {
+    pub fn new(query: &str, page: u32, page_size: u32) -> Self {
+        Cursor {
+            version: CURSOR_VERSION,
+            query: query.to_string(),
+            page,
+            page_size,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        // Serializing a struct of strings and integers cannot fail.
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(token: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .context("Invalid cursor: not a cursor token")?;
+
+        let cursor: Cursor = serde_json::from_slice(&bytes)
+            .context("Invalid cursor: malformed cursor contents")?;
+
+        if cursor.version != CURSOR_VERSION {
+            anyhow::bail!(
+                "Invalid cursor: unsupported cursor version {} (expected {})",
+                cursor.version,
+                CURSOR_VERSION
+            );
+        }
+        if cursor.query.trim().is_empty() {
+            anyhow::bail!("Invalid cursor: missing query");
+        }
+        if cursor.page == 0 || cursor.page_size == 0 || cursor.page_size > 100 {
+            anyhow::bail!("Invalid cursor: page out of range");
+        }
+
+        Ok(cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_token() {
+        let cursor = Cursor::new("climate change", 3, 10);
+        let token = cursor.encode();
+        assert!(!token.contains("climate"));
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+    }
+
+    #[test]
+    fn rejects_garbage_tokens() {
+        assert!(Cursor::decode("not a cursor!").is_err());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode("{}")).is_err());
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let token = URL_SAFE_NO_PAD.encode(r#"{"v":2,"q":"ai","page":2,"page_size":10}"#);
+        let err = Cursor::decode(&token).unwrap_err();
+        assert!(err.to_string().contains("unsupported cursor version 2"));
+    }
+
+    #[test]
+    fn rejects_out_of_range_pages() {
+        let token = URL_SAFE_NO_PAD.encode(r#"{"v":1,"q":"ai","page":0,"page_size":10}"#);
+        assert!(Cursor::decode(&token).is_err());
+
+        let token = URL_SAFE_NO_PAD.encode(r#"{"v":1,"q":"ai","page":1,"page_size":500}"#);
+        assert!(Cursor::decode(&token).is_err());
+    }
+}
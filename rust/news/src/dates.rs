@@ -0,0 +1,105 @@
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+/// Optional `from`/`to` bounds for NewsAPI's `/everything` endpoint.
+#[derive(Debug, Default, PartialEq)]
+pub struct DateRange {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl DateRange {
+    /// Validate both bounds as ISO-8601 and check that `from` is not after `to`.
+    /// Blank values are treated as absent.
+    pub fn new(from: Option<&str>, to: Option<&str>) -> Result<Self> {
+        let from = bound("from-date", from)?;
+        let to = bound("to-date", to)?;
+
+        if let (Some((from_raw, from_at)), Some((to_raw, to_at))) = (&from, &to)
+            && from_at > to_at
+        {
+            anyhow::bail!("from-date {} is after to-date {}", from_raw, to_raw);
+        }
+
+        Ok(Self {
+            from: from.map(|(raw, _)| raw),
+            to: to.map(|(raw, _)| raw),
+        })
+    }
+
+    /// `&from=...&to=...` for the bounds that are set, URL-encoded.
+    pub fn query_params(&self) -> String {
+        let mut params = String::new();
+        for (name, value) in [("from", &self.from), ("to", &self.to)] {
+            if let Some(value) = value {
+                params.push_str(&format!("&{}={}", name, urlencoding::encode(value)));
+            }
+        }
+        params
+    }
+}
+
+/// A trimmed bound and the instant it denotes, or `None` when blank.
+fn bound(name: &str, value: Option<&str>) -> Result<Option<(String, NaiveDateTime)>> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    let at = parse_iso8601(value).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid {} '{}': expected ISO-8601 such as 2024-05-01 or 2024-05-01T08:00:00",
+            name,
+            value
+        )
+    })?;
+    Ok(Some((value.to_string(), at)))
+}
+
+/// The formats NewsAPI accepts: a date, a local date-time, or a date-time with
+/// an offset (normalized to UTC). A bare date means midnight.
+fn parse_iso8601(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0);
+    }
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.naive_utc());
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_dates_and_date_times() {
+        for value in ["2024-05-01", "2024-05-01T08:00", "2024-05-01T08:00:00", "2024-05-01T08:00:00.5", "2024-05-01T08:00:00Z", "2024-05-01T10:00:00+02:00"] {
+            assert!(parse_iso8601(value).is_some(), "{} should parse", value);
+        }
+        for value in ["yesterday", "2024-13-01", "01/05/2024", "2024-05-01 08:00"] {
+            assert!(parse_iso8601(value).is_none(), "{} should not parse", value);
+        }
+    }
+
+    #[test]
+    fn builds_encoded_params() {
+        let range = DateRange::new(Some(" 2024-05-01 "), Some("2024-05-02T10:00:00+02:00")).unwrap();
+        assert_eq!(range.query_params(), "&from=2024-05-01&to=2024-05-02T10%3A00%3A00%2B02%3A00");
+        assert_eq!(DateRange::new(None, Some("")).unwrap(), DateRange::default());
+        assert_eq!(DateRange::default().query_params(), "");
+    }
+
+    #[test]
+    fn rejects_invalid_or_reversed_ranges() {
+        let err = DateRange::new(Some("last week"), None).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid from-date 'last week'"));
+
+        let err = DateRange::new(Some("2024-05-02"), Some("2024-05-01")).unwrap_err();
+        assert_eq!(err.to_string(), "from-date 2024-05-02 is after to-date 2024-05-01");
+
+        // Offsets are compared in UTC: 10:00+02:00 is 08:00Z.
+        assert!(DateRange::new(Some("2024-05-01T09:00:00Z"), Some("2024-05-01T10:00:00+02:00")).is_err());
+        assert!(DateRange::new(Some("2024-05-01"), Some("2024-05-01")).is_ok());
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::BTreeSet;
+
+/// Titles whose word sets overlap at least this much (Jaccard index) are treated
+/// as the same story, e.g. one wire report with a different outlet suffix.
+const SIMILARITY_THRESHOLD: f64 = 0.8;
+/// Titles with fewer words than this only match when identical, since a couple of
+/// shared words says little about short headlines.
+const MIN_FUZZY_WORDS: usize = 4;
+
+/// Indexes of the titles to keep: the first of each group of identical or highly
+/// similar titles, in their original order. Articles without a title are always kept.
+pub fn kept(titles: &[Option<&str>]) -> Vec<usize> {
+    let mut seen: Vec<BTreeSet<String>> = Vec::new();
+    let mut kept = Vec::with_capacity(titles.len());
+
+    for (i, title) in titles.iter().enumerate() {
+        let words = title.map(words).unwrap_or_default();
+        if words.is_empty() {
+            kept.push(i);
+            continue;
+        }
+        if seen.iter().any(|other| same_story(&words, other)) {
+            continue;
+        }
+        seen.push(words);
+        kept.push(i);
+    }
+    kept
+}
+
+/// Lowercased alphanumeric words, ignoring punctuation and spacing.
+fn words(title: &str) -> BTreeSet<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn same_story(a: &BTreeSet<String>, b: &BTreeSet<String>) -> bool {
+    if a == b {
+        return true;
+    }
+    if a.len().min(b.len()) < MIN_FUZZY_WORDS {
+        return false;
+    }
+    let shared = a.intersection(b).count() as f64;
+    let total = a.union(b).count() as f64;
+    shared / total >= SIMILARITY_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_identical_titles_ignoring_case_and_spacing() {
+        let titles = [
+            Some("Tesla recalls 2 million cars"),
+            Some("  TESLA recalls 2   million cars!"),
+            Some("Markets rally"),
+        ];
+        assert_eq!(kept(&titles), [0, 2]);
+    }
+
+    #[test]
+    fn drops_syndicated_copies_with_outlet_suffixes() {
+        let titles = [
+            Some("Central bank holds interest rates steady amid inflation worries"),
+            Some("Central bank holds interest rates steady amid inflation worries - Reuters"),
+            Some("Central bank raises interest rates to fight inflation"),
+        ];
+        assert_eq!(kept(&titles), [0, 2]);
+    }
+
+    #[test]
+    fn keeps_short_and_untitled_articles_unless_identical() {
+        let titles = [Some("Markets rally"), Some("Markets slump"), None, None, Some("markets rally")];
+        assert_eq!(kept(&titles), [0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,106 @@
+use plugin_http::HttpError;
+use std::fmt;
+
+/// What went wrong, as reported across the WIT boundary. The error string is
+/// `"<kind>: <detail>"` with a stable kind prefix, so callers can decide
+/// whether to retry or surface the message without parsing the detail.
+#[derive(Debug, PartialEq)]
+pub enum PluginError {
+    /// NewsAPI could not be reached or the response could not be read.
+    Network(String),
+    /// NewsAPI answered with a non-2xx status not covered below.
+    HttpStatus(u16, String),
+    /// The response body was not the JSON we expected.
+    Parse(String),
+    /// NewsAPI kept answering HTTP 429 after all retries.
+    RateLimited(String),
+    /// An argument, cursor or API key was rejected before any request.
+    InvalidInput(String),
+}
+
+impl PluginError {
+    /// The stable prefix of the WIT error string.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PluginError::Network(_) => "network",
+            PluginError::HttpStatus(..) => "http_status",
+            PluginError::Parse(_) => "parse",
+            PluginError::RateLimited(_) => "rate_limited",
+            PluginError::InvalidInput(_) => "invalid_input",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match self {
+            PluginError::Network(detail)
+            | PluginError::HttpStatus(_, detail)
+            | PluginError::Parse(detail)
+            | PluginError::RateLimited(detail)
+            | PluginError::InvalidInput(detail) => detail,
+        }
+    }
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.detail())
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// Classify an error from an internal function. Errors raised without a kind
+/// are argument checks, so they are reported as `invalid_input`.
+impl From<anyhow::Error> for PluginError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<PluginError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let detail = err.to_string();
+        for cause in err.chain() {
+            match cause.downcast_ref::<HttpError>() {
+                Some(HttpError::Transport { .. } | HttpError::TooLarge { .. }) => return PluginError::Network(detail),
+                Some(HttpError::Status { status: 429, .. }) => return PluginError::RateLimited(detail),
+                Some(HttpError::Status { status, .. }) => return PluginError::HttpStatus(*status, detail),
+                None => {}
+            }
+            if cause.is::<serde_json::Error>() {
+                return PluginError::Parse(detail);
+            }
+        }
+        PluginError::InvalidInput(detail)
+    }
+}
+
+/// The WIT error string for an error from an internal function, with `what`
+/// failed leading the detail, e.g. `"network: News search failed: ..."`.
+/// Logged first when `NOORLE_DEBUG` is on.
+pub fn to_wit(err: anyhow::Error, what: &str) -> String {
+    let err = PluginError::from(err);
+    let detail = format!("{}: {}", what, err.detail());
+    plugin_http::debug_log!("error", kind = err.kind(), detail = detail);
+    format!("{}: {}", err.kind(), detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn prefixes_wit_errors_with_a_stable_kind() {
+        let transport = anyhow::Error::new(HttpError::Transport { message: "connection refused".to_string(), attempts: 2 })
+            .context("Failed to send request to NewsAPI");
+        assert_eq!(to_wit(transport, "News search failed"), "network: News search failed: Failed to send request to NewsAPI");
+
+        let parse = serde_json::from_str::<serde_json::Value>("{").context("Failed to parse NewsAPI JSON response").unwrap_err();
+        assert_eq!(to_wit(parse, "News search failed"), "parse: News search failed: Failed to parse NewsAPI JSON response");
+
+        let rate_limited = anyhow::Error::from(PluginError::RateLimited("NewsAPI rate limit exceeded".to_string()));
+        assert_eq!(to_wit(rate_limited, "Top headlines failed"), "rate_limited: Top headlines failed: NewsAPI rate limit exceeded");
+
+        let invalid = anyhow::anyhow!("Search query cannot be empty");
+        assert_eq!(to_wit(invalid, "News search failed"), "invalid_input: News search failed: Search query cannot be empty");
+    }
+}
@@ -1,99 +1,780 @@
 #![allow(unsafe_op_in_unsafe_fn)]
 
+mod cluster;
+mod cursor;
+mod dates;
+mod dedupe;
+mod error;
+mod retry;
 mod types;
 
 use anyhow::{Context, Result};
-use std::time::Duration;
-use types::NewsApiResponse;
-use waki::Client;
+use chrono::SecondsFormat;
+use cursor::Cursor;
+use dates::DateRange;
+use error::PluginError;
+use plugin_http::HttpError;
+use std::time::{Duration, Instant};
+use types::{NewsApiArticle, NewsApiError, NewsApiResponse, NewsApiSource};
 
-wit_bindgen::generate!({
-    world: "news-component",
-    path: "./wit",
-});
+mod bindings {
+    // The generated export shims take one argument per flattened WIT parameter.
+    #![allow(clippy::too_many_arguments)]
+
+    wit_bindgen::generate!({
+        world: "news-component",
+        path: "./wit",
+    });
+}
+
+use bindings::*;
 
 const NEWSAPI_ENDPOINT: &str = "https://newsapi.org/v2/everything";
+const TOP_HEADLINES_ENDPOINT: &str = "https://newsapi.org/v2/top-headlines";
+/// The categories NewsAPI's top-headlines endpoint accepts.
+const HEADLINE_CATEGORIES: &[&str] = &["business", "entertainment", "general", "health", "science", "sports", "technology"];
 const TIMEOUT_SECS: u64 = 30;
+const API_KEY_VAR: &str = "NEWSAPI_API_KEY";
+/// Source name reported when NewsAPI identifies a source but leaves its name null.
+const UNKNOWN_SOURCE: &str = "unknown";
 const DEFAULT_PAGE_SIZE: u32 = 10;
+const MAX_PAGE_SIZE: u32 = 100;
+/// NewsAPI rejects `q` values longer than this.
+const MAX_QUERY_CHARS: usize = 500;
+/// NewsAPI's developer plan stops serving results past the 100th article.
+const MAX_REACHABLE_RESULTS: u32 = 100;
+/// Two-letter article languages NewsAPI's `/everything` endpoint accepts.
+const LANGUAGES: &[&str] = &["ar", "de", "en", "es", "fr", "he", "it", "nl", "no", "pt", "ru", "sv", "ud", "zh"];
+/// NewsAPI accepts at most this many comma-separated `sources`.
+const MAX_SOURCES: usize = 20;
+/// Article fields NewsAPI's `searchIn` can restrict the query to.
+const SEARCH_IN_FIELDS: &[&str] = &["title", "description", "content"];
 
-fn search_news_internal(query: String) -> Result<NewsResponse> {
-    // Get API key from environment variable
-    let api_key = std::env::var("NEWSAPI_API_KEY")
-        .context("NEWSAPI_API_KEY environment variable not set")?;
+/// Filters and ordering applied on top of the query. The default leaves every
+/// choice to NewsAPI.
+///
+/// NewsAPI refuses `sources` alongside `country` or `category`. Those belong to
+/// `/top-headlines` and aren't sent from here, so no such combination can occur;
+/// if they are ever added, reject it when building the filters.
+#[derive(Default)]
+struct SearchFilters {
+    dates: DateRange,
+    language: Option<&'static str>,
+    sort_by: Option<SortBy>,
+    /// NewsAPI source ids such as `bbc-news`.
+    sources: Vec<String>,
+    /// Bare domains such as `bbc.co.uk`.
+    domains: Vec<String>,
+    /// Fields the query is matched against; empty for all of them.
+    search_in: Vec<&'static str>,
+}
 
-    if api_key.is_empty() {
-        anyhow::bail!("NEWSAPI_API_KEY is empty");
+impl SearchFilters {
+    /// Extra query-string parameters for the filters that are set.
+    fn query_params(&self) -> String {
+        let mut params = self.dates.query_params();
+        if let Some(language) = self.language {
+            params.push_str(&format!("&language={}", language));
+        }
+        if let Some(sort_by) = self.sort_by {
+            params.push_str(&format!("&sortBy={}", sort_by_param(sort_by)));
+        }
+        for (name, values) in [("sources", &self.sources), ("domains", &self.domains)] {
+            if !values.is_empty() {
+                params.push_str(&format!("&{}={}", name, urlencoding::encode(&values.join(","))));
+            }
+        }
+        if !self.search_in.is_empty() {
+            params.push_str(&format!("&searchIn={}", urlencoding::encode(&self.search_in.join(","))));
+        }
+        params
     }
+}
 
-    // Encode the query parameter
-    let encoded_query = urlencoding::encode(&query);
+fn search_news_internal(query: String, options: SearchOptions) -> Result<NewsResponse> {
+    let query = validate_query(&query)?;
+    let full_query = with_exclusions(query, &options.exclude_terms)?;
+    let sources = outlet_list(options.sources);
+    if sources.len() > MAX_SOURCES {
+        anyhow::bail!("At most {} sources can be given, got {}", MAX_SOURCES, sources.len());
+    }
+    let filters = SearchFilters {
+        dates: DateRange::new(options.from_date.as_deref(), options.to_date.as_deref())?,
+        language: options.language.as_deref().map(validate_language).transpose()?,
+        sort_by: options.sort_by,
+        sources,
+        domains: outlet_list(options.domains),
+        search_in: options.search_in.as_deref().map(search_in_fields).transpose()?.unwrap_or_default(),
+    };
+    let page_size = effective_page_size(options.page_size);
+    let page = fetch_page(&full_query, reachable_page(options.page, page_size)?, page_size, &filters, "")?;
 
-    // Build the request URL
-    let request_url = format!(
-        "{}?q={}&pageSize={}",
-        NEWSAPI_ENDPOINT, encoded_query, DEFAULT_PAGE_SIZE
-    );
+    let mut articles = page.articles;
+    let duplicates_removed = options.dedupe.then(|| dedupe_articles(&mut articles));
+    Ok(NewsResponse {
+        clusters: options.cluster.then(|| cluster_articles(query, &articles)),
+        articles,
+        total_results: page.total_results,
+        elapsed_ms: options.with_metrics.then_some(page.elapsed_ms),
+        duplicates_removed,
+    })
+}
+
+/// Drop articles whose titles repeat an earlier one, keeping the first of
+/// each, and return how many were removed.
+fn dedupe_articles(articles: &mut Vec<Article>) -> u32 {
+    let titles: Vec<Option<&str>> = articles.iter().map(|article| article.title.as_deref()).collect();
+    let kept = dedupe::kept(&titles);
+    let removed = articles.len() - kept.len();
+
+    let mut index = 0;
+    articles.retain(|_| {
+        let keep = kept.binary_search(&index).is_ok();
+        index += 1;
+        keep
+    });
+    u32::try_from(removed).unwrap_or(u32::MAX)
+}
+
+/// Group articles into labeled sub-topic clusters by shared title/description terms.
+fn cluster_articles(query: &str, articles: &[Article]) -> Vec<ArticleCluster> {
+    let texts: Vec<(Option<&str>, Option<&str>)> = articles
+        .iter()
+        .map(|article| (article.title.as_deref(), article.description.as_deref()))
+        .collect();
+
+    cluster::group(query, &texts)
+        .into_iter()
+        .map(|group| ArticleCluster {
+            label: group.label,
+            terms: group.terms,
+            articles: group.members.into_iter().map(|i| articles[i].clone()).collect(),
+        })
+        .collect()
+}
 
-    // Make the HTTP request
-    let response = Client::new()
-        .get(&request_url)
-        .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-        .header("x-api-key", &api_key)
-        .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-        .send()
-        .context("Failed to send request to NewsAPI")?;
+/// Trim a search query and reject ones NewsAPI would refuse or can't match.
+///
+/// Case is preserved: NewsAPI treats uppercase `AND`/`OR`/`NOT` as operators.
+fn validate_query(query: &str) -> Result<&str> {
+    let query = query.trim();
 
-    let status = response.status_code();
+    if query.is_empty() {
+        anyhow::bail!("Search query cannot be empty");
+    }
+    if query.chars().count() > MAX_QUERY_CHARS {
+        anyhow::bail!("Search query is too long (max {} characters)", MAX_QUERY_CHARS);
+    }
+    if !query.chars().any(char::is_alphanumeric) {
+        anyhow::bail!("Search query must contain at least one letter or digit");
+    }
 
-    // Handle rate limiting
-    if status == 429 {
-        anyhow::bail!("NewsAPI rate limit exceeded. Please try again later.");
+    Ok(query)
+}
+
+/// Append a NewsAPI `-term` exclusion per term to an already validated query.
+/// Terms of several words are quoted so the whole phrase is excluded; blank
+/// terms are skipped. The combined query must still fit NewsAPI's length limit.
+fn with_exclusions(query: &str, exclude_terms: &[String]) -> Result<String> {
+    let mut full_query = query.to_string();
+    for term in exclude_terms {
+        let term = term.trim().trim_start_matches('-').replace('"', "");
+        let term = term.trim();
+        if term.is_empty() {
+            continue;
+        }
+        if term.contains(char::is_whitespace) {
+            full_query.push_str(&format!(" -\"{}\"", term));
+        } else {
+            full_query.push_str(&format!(" -{}", term));
+        }
+    }
+    if full_query.chars().count() > MAX_QUERY_CHARS {
+        anyhow::bail!(
+            "Search query with exclusions is too long (max {} characters)",
+            MAX_QUERY_CHARS
+        );
     }
+    Ok(full_query)
+}
 
-    // Handle authentication errors
-    if status == 401 {
-        anyhow::bail!("Invalid NewsAPI API key");
+/// Parse a comma-separated `searchIn` list such as "title,description",
+/// case-insensitively and without repeats, in NewsAPI's field order.
+fn search_in_fields(value: &str) -> Result<Vec<&'static str>> {
+    let mut requested = Vec::new();
+    for field in value.split(',').map(|field| field.trim().to_ascii_lowercase()) {
+        if field.is_empty() {
+            continue;
+        }
+        let field = SEARCH_IN_FIELDS
+            .iter()
+            .find(|&&known| known == field)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported search-in field '{}': expected any of {}", field, SEARCH_IN_FIELDS.join(", ")))?;
+        requested.push(*field);
     }
+    Ok(SEARCH_IN_FIELDS.iter().copied().filter(|field| requested.contains(field)).collect())
+}
 
-    // Check for other HTTP errors
-    if !(200..300).contains(&status) {
-        anyhow::bail!("NewsAPI returned HTTP status code: {}", status);
+/// Match a language code case-insensitively against the ones NewsAPI supports.
+fn validate_language(language: &str) -> Result<&'static str> {
+    let language = language.trim().to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|&&code| code == language)
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("Unsupported language '{}': expected one of {}", language, LANGUAGES.join(", ")))
+}
+
+/// Trimmed, lowercased and de-duplicated source ids or domains, in the order given.
+fn outlet_list(values: Vec<String>) -> Vec<String> {
+    let mut outlets: Vec<String> = Vec::with_capacity(values.len());
+    for value in values {
+        let value = value.trim().to_ascii_lowercase();
+        if !value.is_empty() && !outlets.contains(&value) {
+            outlets.push(value);
+        }
     }
+    outlets
+}
 
-    // Read response body
-    let body_bytes = response
-        .body()
-        .context("Failed to read response body")?;
+fn sort_by_param(sort_by: SortBy) -> &'static str {
+    match sort_by {
+        SortBy::Relevancy => "relevancy",
+        SortBy::Popularity => "popularity",
+        SortBy::PublishedAt => "publishedAt",
+    }
+}
+
+/// Page size to request: 0 means the default, anything else is capped at NewsAPI's maximum.
+fn effective_page_size(page_size: u32) -> u32 {
+    match page_size {
+        0 => DEFAULT_PAGE_SIZE,
+        size => size.min(MAX_PAGE_SIZE),
+    }
+}
+
+/// Page number to request: 0 means the first page. Pages that start past the
+/// results NewsAPI will serve are rejected rather than sent.
+fn reachable_page(page: u32, page_size: u32) -> Result<u32> {
+    let page = page.max(1);
+    let skipped = (page - 1).saturating_mul(page_size);
+    if skipped >= MAX_REACHABLE_RESULTS {
+        anyhow::bail!(
+            "Page {} of size {} is past the first {} results, which is all NewsAPI serves",
+            page,
+            page_size,
+            MAX_REACHABLE_RESULTS
+        );
+    }
+    Ok(page)
+}
+
+/// Search with a caller-supplied NewsAPI key, for hosts that serve several
+/// tenants from one deployment. An empty key falls back to `NEWSAPI_API_KEY`.
+fn search_news_with_key_internal(query: String, api_key: String) -> Result<NewsResponse> {
+    let query = validate_query(&query)?;
+    let page = fetch_page(query, 1, DEFAULT_PAGE_SIZE, &SearchFilters::default(), &api_key)?;
+    Ok(NewsResponse {
+        articles: page.articles,
+        total_results: page.total_results,
+        elapsed_ms: None,
+        clusters: None,
+        duplicates_removed: None,
+    })
+}
+
+/// Curated breaking news from NewsAPI's top-headlines endpoint for a country,
+/// a category, a query, or any combination of them.
+fn get_top_headlines_internal(country: Option<String>, category: Option<String>, query: Option<String>) -> Result<NewsResponse> {
+    let request_url = top_headlines_url(country.as_deref(), category.as_deref(), query.as_deref())?;
+    let page = request_articles(&request_url, "")?;
+    Ok(NewsResponse {
+        articles: page.articles,
+        total_results: page.total_results,
+        elapsed_ms: None,
+        clusters: None,
+        duplicates_removed: None,
+    })
+}
+
+/// Validate the top-headlines filters and build the request URL. Blank values
+/// count as absent, and at least one filter is required.
+fn top_headlines_url(country: Option<&str>, category: Option<&str>, query: Option<&str>) -> Result<String> {
+    let mut params = Vec::new();
+
+    if let Some(country) = present(country) {
+        if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+            anyhow::bail!("Invalid country '{}': expected a two-letter code such as 'us' or 'gb'", country);
+        }
+        params.push(format!("country={}", country.to_ascii_lowercase()));
+    }
+    if let Some(category) = present(category) {
+        let category = category.to_ascii_lowercase();
+        if !HEADLINE_CATEGORIES.contains(&category.as_str()) {
+            anyhow::bail!("Unknown category '{}': expected one of {}", category, HEADLINE_CATEGORIES.join(", "));
+        }
+        params.push(format!("category={}", category));
+    }
+    if let Some(query) = present(query) {
+        params.push(format!("q={}", urlencoding::encode(validate_query(query)?)));
+    }
+
+    if params.is_empty() {
+        anyhow::bail!("Top headlines need at least a country, a category or a query");
+    }
+    Ok(format!("{}?{}", TOP_HEADLINES_ENDPOINT, params.join("&")))
+}
+
+/// A trimmed optional value, with blank treated as absent.
+fn present(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// Fetch one page of results, resuming from `cursor` or starting fresh when it is empty.
+fn search_news_paged_internal(query: String, cursor: String) -> Result<NewsPage> {
+    let query = query.trim();
+
+    let position = if cursor.trim().is_empty() {
+        Cursor::new(validate_query(query)?, 1, DEFAULT_PAGE_SIZE)
+    } else {
+        let position = Cursor::decode(&cursor)?;
+        if !query.is_empty() && query != position.query {
+            anyhow::bail!(
+                "Cursor was issued for query '{}', not '{}'; pass an empty cursor to start a new search",
+                position.query,
+                query
+            );
+        }
+        position
+    };
+
+    let page = fetch_page(&position.query, position.page, position.page_size, &SearchFilters::default(), "")?;
+
+    Ok(NewsPage {
+        articles: page.articles,
+        next_cursor: next_position(&position, page.total_results).map(|next| next.encode()),
+    })
+}
+
+/// The page after `position`, if NewsAPI can still serve it.
+fn next_position(position: &Cursor, total_results: u32) -> Option<Cursor> {
+    let fetched = position.page.saturating_mul(position.page_size);
+    (fetched < total_results.min(MAX_REACHABLE_RESULTS))
+        .then(|| Cursor::new(&position.query, position.page + 1, position.page_size))
+}
+
+/// One page of NewsAPI results.
+struct FetchedPage {
+    articles: Vec<Article>,
+    /// NewsAPI's `totalResults` for the whole query.
+    total_results: u32,
+    /// Time spent sending the request and reading the response.
+    elapsed_ms: u32,
+}
+
+/// Request a single page of search results with `filters` applied, authenticating
+/// with `api_key` or, when it is empty, `NEWSAPI_API_KEY`.
+fn fetch_page(query: &str, page: u32, page_size: u32, filters: &SearchFilters, api_key: &str) -> Result<FetchedPage> {
+    // Encode the query parameter
+    let encoded_query = urlencoding::encode(query);
+
+    // Build the request URL
+    let request_url = format!(
+        "{}?q={}&page={}&pageSize={}{}",
+        NEWSAPI_ENDPOINT,
+        encoded_query,
+        page,
+        page_size,
+        filters.query_params()
+    );
+
+    request_articles(&request_url, api_key)
+}
+
+/// GET a NewsAPI URL and map the articles to WIT types.
+fn request_articles(request_url: &str, api_key: &str) -> Result<FetchedPage> {
+    let api_key = resolve_api_key(api_key)?;
+
+    // Make the HTTP request, retrying rate limits, server errors and connection failures
+    let started = Instant::now();
+    let headers = [("x-api-key", api_key.as_str())];
+    let retries = retry::max_attempts() - 1;
+    let body_bytes = match plugin_http::get_with_retry(request_url, &headers, Duration::from_secs(TIMEOUT_SECS), retries) {
+        Ok(body_bytes) => body_bytes,
+        Err(HttpError::Status { status: 429, attempts, .. }) => {
+            return Err(PluginError::RateLimited(format!(
+                "NewsAPI rate limit exceeded after {} attempt(s). Please try again later.",
+                attempts
+            ))
+            .into())
+        }
+        // Handle authentication errors
+        Err(HttpError::Status { status: 401, .. }) => {
+            return Err(PluginError::HttpStatus(401, "Invalid NewsAPI API key".to_string()).into())
+        }
+        // Check for other HTTP errors, passing on NewsAPI's own explanation
+        Err(HttpError::Status { status, body, .. }) => return Err(error_from_body(status, &body).into()),
+        Err(e) => return Err(anyhow::Error::new(e).context("Failed to send request to NewsAPI")),
+    };
+    let elapsed_ms = u32::try_from(started.elapsed().as_millis()).unwrap_or(u32::MAX);
 
     // Parse JSON response
     let api_response: NewsApiResponse = serde_json::from_slice(&body_bytes)
         .context("Failed to parse NewsAPI JSON response")?;
 
+    if api_response.status != "ok" {
+        return Err(PluginError::Parse(format!("NewsAPI returned status: {}", api_response.status)).into());
+    }
+
     // Convert to WIT-generated types
-    let articles: Vec<Article> = api_response
-        .articles
-        .into_iter()
-        .map(|article| Article {
-            title: article.title,
-            description: article.description,
-            url: article.url,
-            source: article.source.map(|s| Source { name: s.name }),
-        })
-        .collect();
+    let articles: Vec<Article> = api_response.articles.into_iter().map(map_article).collect();
+
+    Ok(FetchedPage {
+        articles,
+        total_results: api_response.total_results,
+        elapsed_ms,
+    })
+}
+
+/// NewsAPI's `code`/`message` from an error body, falling back to the bare
+/// HTTP status when the body isn't NewsAPI's error JSON.
+fn error_from_body(status: u16, body: &[u8]) -> PluginError {
+    let Ok(NewsApiError { code, message }) = serde_json::from_slice::<NewsApiError>(body) else {
+        return PluginError::HttpStatus(status, format!("NewsAPI returned HTTP status code: {}", status));
+    };
+    let detail = match (code, message) {
+        (Some(code), Some(message)) => format!("NewsAPI error ({}): {}", code, message),
+        (Some(code), None) => format!("NewsAPI error ({}) with HTTP status code {}", code, status),
+        (None, Some(message)) => format!("NewsAPI error: {}", message),
+        (None, None) => format!("NewsAPI returned HTTP status code: {}", status),
+    };
+    PluginError::HttpStatus(status, detail)
+}
+
+/// The supplied key when it is non-blank, otherwise the one from the environment.
+fn resolve_api_key(supplied: &str) -> Result<String> {
+    let supplied = supplied.trim();
+    if !supplied.is_empty() {
+        return Ok(supplied.to_string());
+    }
+
+    let api_key = std::env::var(API_KEY_VAR)
+        .with_context(|| format!("{} environment variable not set", API_KEY_VAR))?;
+
+    if api_key.is_empty() {
+        anyhow::bail!("{} is empty", API_KEY_VAR);
+    }
+    Ok(api_key)
+}
+
+fn map_article(article: NewsApiArticle) -> Article {
+    Article {
+        title: article.title,
+        description: article.description,
+        url: article.url,
+        source: article.source.map(map_source),
+        author: article.author.filter(|author| !author.trim().is_empty()),
+        content: article.content,
+        published_at: article.published_at.map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        published_at_unix: article.published_at.map(|at| at.timestamp()),
+    }
+}
+
+/// A present source always gets a name, so `None` on the article only ever
+/// means NewsAPI sent no source at all.
+fn map_source(source: NewsApiSource) -> Source {
+    let name = source
+        .name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| UNKNOWN_SOURCE.to_string());
+
+    Source {
+        id: source.id.filter(|id| !id.trim().is_empty()),
+        name,
+    }
+}
 
-    Ok(NewsResponse { articles })
+fn required_config() -> Vec<ConfigVar> {
+    vec![
+        ConfigVar {
+            name: API_KEY_VAR.to_string(),
+            required: true,
+            description: "NewsAPI.org API key, from https://newsapi.org/ (search-news-with-key can pass one per call instead)".to_string(),
+        },
+        ConfigVar {
+            name: retry::MAX_ATTEMPTS_VAR.to_string(),
+            required: false,
+            description: format!(
+                "Attempts per request on rate limiting (429), server errors (5xx) or connection failures, honoring Retry-After (default {})",
+                retry::DEFAULT_MAX_ATTEMPTS
+            ),
+        },
+        ConfigVar {
+            name: plugin_http::debug::DEBUG_VAR.to_string(),
+            required: false,
+            description: "Set to 1 to log requests, responses, retries and errors to stderr".to_string(),
+        },
+    ]
 }
 
 struct NewsComponent;
 
 impl Guest for NewsComponent {
-    fn search_news(query: String) -> Result<NewsResponse, String> {
-        if query.trim().is_empty() {
-            return Err("Search query cannot be empty".to_string());
-        }
+    fn search_news(query: String, options: SearchOptions) -> Result<NewsResponse, String> {
+        search_news_internal(query, options).map_err(|e| error::to_wit(e, "News search failed"))
+    }
+
+    fn search_news_with_key(query: String, api_key: String) -> Result<NewsResponse, String> {
+        search_news_with_key_internal(query, api_key).map_err(|e| error::to_wit(e, "News search failed"))
+    }
+
+    fn get_top_headlines(country: Option<String>, category: Option<String>, query: Option<String>) -> Result<NewsResponse, String> {
+        get_top_headlines_internal(country, category, query).map_err(|e| error::to_wit(e, "Top headlines failed"))
+    }
 
-        search_news_internal(query).map_err(|e| format!("News search failed: {}", e))
+    fn search_news_paged(query: String, cursor: String) -> Result<NewsPage, String> {
+        search_news_paged_internal(query, cursor).map_err(|e| error::to_wit(e, "News search failed"))
+    }
+
+    fn required_config() -> Vec<ConfigVar> {
+        required_config()
     }
 }
 
-export!(NewsComponent);
+export!(NewsComponent with_types_in bindings);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_and_validates_queries() {
+        assert_eq!(validate_query("  climate change \n").unwrap(), "climate change");
+        assert_eq!(validate_query("AI OR \"machine learning\"").unwrap(), "AI OR \"machine learning\"");
+        assert!(validate_query("   ").is_err());
+        assert!(validate_query("?!... --").is_err());
+
+        let too_long = "a".repeat(MAX_QUERY_CHARS + 1);
+        assert!(validate_query(&too_long).unwrap_err().to_string().contains("too long"));
+        assert!(validate_query(&too_long[1..]).is_ok());
+    }
+
+    #[test]
+    fn defaults_and_caps_page_size() {
+        assert_eq!(effective_page_size(0), DEFAULT_PAGE_SIZE);
+        assert_eq!(effective_page_size(25), 25);
+        assert_eq!(effective_page_size(500), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn validates_language_codes() {
+        assert_eq!(validate_language(" EN ").unwrap(), "en");
+        assert_eq!(validate_language("zh").unwrap(), "zh");
+        let err = validate_language("english").unwrap_err();
+        assert!(err.to_string().starts_with("Unsupported language 'english': expected one of ar, de, en"));
+    }
+
+    #[test]
+    fn adds_only_the_filters_that_are_set() {
+        assert_eq!(SearchFilters::default().query_params(), "");
+        let filters = SearchFilters {
+            dates: DateRange::new(Some("2024-05-01"), None).unwrap(),
+            language: Some("en"),
+            sort_by: Some(SortBy::PublishedAt),
+            sources: vec!["bbc-news".to_string(), "the-verge".to_string()],
+            domains: vec!["bbc.co.uk".to_string()],
+            search_in: vec!["title", "description"],
+        };
+        assert_eq!(
+            filters.query_params(),
+            "&from=2024-05-01&language=en&sortBy=publishedAt&sources=bbc-news%2Cthe-verge&domains=bbc.co.uk&searchIn=title%2Cdescription"
+        );
+    }
+
+    #[test]
+    fn appends_exclusions_to_the_query() {
+        let terms = vec!["fruit".to_string(), " -pie ".to_string(), "apple \"cider\" vinegar".to_string(), "  ".to_string()];
+        assert_eq!(with_exclusions("apple", &terms).unwrap(), "apple -fruit -pie -\"apple cider vinegar\"");
+        assert_eq!(with_exclusions("apple", &[]).unwrap(), "apple");
+
+        let long = vec!["x".repeat(MAX_QUERY_CHARS)];
+        assert!(with_exclusions("apple", &long).unwrap_err().to_string().contains("too long"));
+    }
+
+    #[test]
+    fn parses_search_in_fields() {
+        assert_eq!(search_in_fields("Content, title,title").unwrap(), ["title", "content"]);
+        assert!(search_in_fields(" , ").unwrap().is_empty());
+        let err = search_in_fields("title,body").unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported search-in field 'body': expected any of title, description, content");
+    }
+
+    #[test]
+    fn normalizes_outlet_lists() {
+        let outlets = outlet_list(vec![" BBC-News ".to_string(), String::new(), "bbc-news".to_string(), "the-verge".to_string()]);
+        assert_eq!(outlets, ["bbc-news", "the-verge"]);
+    }
+
+    #[test]
+    fn builds_top_headlines_urls() {
+        assert_eq!(
+            top_headlines_url(Some(" US "), Some("Technology"), Some("AI chips")).unwrap(),
+            "https://newsapi.org/v2/top-headlines?country=us&category=technology&q=AI%20chips"
+        );
+        assert_eq!(
+            top_headlines_url(None, Some("sports"), Some("  ")).unwrap(),
+            "https://newsapi.org/v2/top-headlines?category=sports"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_top_headlines_filters() {
+        assert!(top_headlines_url(Some("usa"), None, None).unwrap_err().to_string().contains("two-letter"));
+        assert!(top_headlines_url(None, Some("politics"), None).unwrap_err().to_string().starts_with("Unknown category 'politics'"));
+        assert!(top_headlines_url(Some(""), None, None).unwrap_err().to_string().contains("at least"));
+    }
+
+    #[test]
+    fn defaults_and_bounds_page_number() {
+        assert_eq!(reachable_page(0, 10).unwrap(), 1);
+        assert_eq!(reachable_page(3, 10).unwrap(), 3);
+        assert_eq!(reachable_page(10, 10).unwrap(), 10);
+        assert!(reachable_page(11, 10).unwrap_err().to_string().contains("first 100 results"));
+        assert!(reachable_page(2, 100).is_err());
+        assert!(reachable_page(u32::MAX, 100).is_err());
+    }
+
+    #[test]
+    fn advances_until_results_run_out() {
+        let first = Cursor::new("ai", 1, 10);
+        assert_eq!(next_position(&first, 25), Some(Cursor::new("ai", 2, 10)));
+        assert_eq!(next_position(&Cursor::new("ai", 3, 10), 25), None);
+        assert_eq!(next_position(&first, 10), None);
+        assert_eq!(next_position(&first, 0), None);
+    }
+
+    #[test]
+    fn stops_at_newsapi_result_cap() {
+        assert_eq!(next_position(&Cursor::new("ai", 9, 10), 4000), Some(Cursor::new("ai", 10, 10)));
+        assert_eq!(next_position(&Cursor::new("ai", 10, 10), 4000), None);
+    }
+
+    #[test]
+    fn prefers_the_supplied_api_key() {
+        assert_eq!(resolve_api_key("  tenant-key ").unwrap(), "tenant-key");
+    }
+
+    #[test]
+    fn reports_newsapi_error_code_and_message() {
+        let body = br#"{"status": "error", "code": "parameterInvalid", "message": "You are trying to request results too far in the past."}"#;
+        assert_eq!(
+            error_from_body(426, body).to_string(),
+            "http_status: NewsAPI error (parameterInvalid): You are trying to request results too far in the past."
+        );
+        assert_eq!(
+            error_from_body(400, br#"{"status": "error", "code": "sourcesTooMany"}"#).to_string(),
+            "http_status: NewsAPI error (sourcesTooMany) with HTTP status code 400"
+        );
+        assert_eq!(error_from_body(502, b"<html>Bad gateway</html>").to_string(), "http_status: NewsAPI returned HTTP status code: 502");
+    }
+
+    #[test]
+    fn reports_api_key_as_required_config() {
+        let config = required_config();
+        assert_eq!(config.len(), 3);
+        assert_eq!(config[0].name, "NEWSAPI_API_KEY");
+        assert!(config[0].required);
+        assert_eq!(config[1].name, "NEWSAPI_MAX_RETRIES");
+        assert!(!config[1].required);
+        assert_eq!(config[2].name, "NOORLE_DEBUG");
+        assert!(!config[2].required);
+    }
+
+    fn parse_article(json: &str) -> NewsApiArticle {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn keeps_source_id_and_name() {
+        let article = map_article(parse_article(
+            r#"{"source": {"id": "bbc-news", "name": "BBC News"}, "title": "t", "description": null, "url": null}"#,
+        ));
+        let source = article.source.unwrap();
+        assert_eq!(source.id.as_deref(), Some("bbc-news"));
+        assert_eq!(source.name, "BBC News");
+    }
+
+    #[test]
+    fn names_present_sources_without_a_name_unknown() {
+        let article = map_article(parse_article(
+            r#"{"source": {"id": null, "name": null}, "title": "t", "description": null, "url": null}"#,
+        ));
+        let source = article.source.unwrap();
+        assert_eq!(source.id, None);
+        assert_eq!(source.name, UNKNOWN_SOURCE);
+    }
+
+    #[test]
+    fn clusters_carry_their_articles() {
+        let articles: Vec<Article> = [
+            r#"{"title": "Tesla recall widens", "description": null, "url": "https://a"}"#,
+            r#"{"title": "Markets rally", "description": null, "url": "https://b"}"#,
+            r#"{"title": "Tesla recall explained", "description": null, "url": "https://c"}"#,
+        ]
+        .into_iter()
+        .map(|json| map_article(parse_article(json)))
+        .collect();
+
+        let clusters = cluster_articles("cars", &articles);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].label, "recall / tesla");
+        let urls: Vec<_> = clusters[0].articles.iter().map(|a| a.url.as_deref().unwrap()).collect();
+        assert_eq!(urls, ["https://a", "https://c"]);
+        assert_eq!(clusters[1].label, cluster::OTHER_LABEL);
+    }
+
+    #[test]
+    fn maps_author_content_and_publication_time() {
+        let article = map_article(parse_article(
+            r#"{"title": "t", "description": null, "url": null, "author": "Jane Doe",
+                "content": "Body [+120 chars]", "publishedAt": "2024-05-01T10:30:00+02:00"}"#,
+        ));
+        assert_eq!(article.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(article.content.as_deref(), Some("Body [+120 chars]"));
+        assert_eq!(article.published_at.as_deref(), Some("2024-05-01T08:30:00Z"));
+        assert_eq!(article.published_at_unix, Some(1714552200));
+    }
+
+    #[test]
+    fn drops_blank_authors_and_unparseable_dates() {
+        let article = map_article(parse_article(
+            r#"{"title": "t", "description": null, "url": null, "author": " ", "publishedAt": "yesterday"}"#,
+        ));
+        assert_eq!(article.author, None);
+        assert_eq!(article.published_at, None);
+        assert_eq!(article.published_at_unix, None);
+    }
+
+    #[test]
+    fn dedupes_articles_and_counts_removals() {
+        let mut articles: Vec<Article> = [
+            r#"{"title": "Tesla recall widens to 2 million cars", "description": null, "url": "https://a"}"#,
+            r#"{"title": "Markets rally", "description": null, "url": "https://b"}"#,
+            r#"{"title": "Tesla recall widens to 2 million cars - Reuters", "description": null, "url": "https://c"}"#,
+        ]
+        .into_iter()
+        .map(|json| map_article(parse_article(json)))
+        .collect();
+
+        assert_eq!(dedupe_articles(&mut articles), 1);
+        let urls: Vec<_> = articles.iter().map(|a| a.url.as_deref().unwrap()).collect();
+        assert_eq!(urls, ["https://a", "https://b"]);
+    }
+
+    #[test]
+    fn leaves_absent_sources_as_none() {
+        let article = map_article(parse_article(r#"{"title": "t", "description": null, "url": null}"#));
+        assert!(article.source.is_none());
+    }
+}
@@ -1,12 +1,22 @@
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
 
 /// Response from NewsAPI.org
 #[derive(Debug, Deserialize)]
 pub struct NewsApiResponse {
     pub status: String,
+    #[serde(rename = "totalResults", default)]
+    pub total_results: u32,
     pub articles: Vec<NewsApiArticle>,
 }
 
+/// Error body NewsAPI.org sends with `"status": "error"`
+#[derive(Debug, Deserialize)]
+pub struct NewsApiError {
+    pub code: Option<String>,
+    pub message: Option<String>,
+}
+
 /// Article from NewsAPI.org
 #[derive(Debug, Deserialize)]
 pub struct NewsApiArticle {
@@ -14,10 +24,30 @@ pub struct NewsApiArticle {
     pub title: Option<String>,
     pub description: Option<String>,
     pub url: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Article body, which NewsAPI truncates to about 200 characters.
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(rename = "publishedAt", default, deserialize_with = "lenient_timestamp")]
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// Parse an RFC 3339 timestamp, treating a missing or malformed value as absent
+/// so one bad date doesn't fail the whole response.
+fn lenient_timestamp<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value
+        .and_then(|value| DateTime::parse_from_rfc3339(value.trim()).ok())
+        .map(|at| at.with_timezone(&Utc)))
 }
 
 /// Source information from NewsAPI.org
 #[derive(Debug, Deserialize)]
 pub struct NewsApiSource {
+    pub id: Option<String>,
     pub name: Option<String>,
 }
@@ -0,0 +1,162 @@
+//! Opt-in diagnostics on stderr. Set `NOORLE_DEBUG=1` and every plugin
+//! writes one line per event in the same `key=value` format, e.g.
+//!
+//! ```text
+//! [noorle-debug] ts=1758560400123 source=plugin-http event=response url="https://api.example.com/v1?q=x" status=503 elapsed_ms=84
+//! ```
+//!
+//! `source` is the crate that logged the line. Use [`debug_log!`](crate::debug_log)
+//! rather than calling [`log`] directly, so `source` is filled in and the
+//! fields are only formatted when logging is on.
+
+use std::fmt::{self, Display};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable that turns the debug lines on.
+pub const DEBUG_VAR: &str = "NOORLE_DEBUG";
+
+/// Query parameters whose values are replaced by `REDACTED` in logged URLs.
+const SECRET_PARAMS: &[&str] = &["appid", "apikey", "api_key", "key", "token"];
+
+/// Whether `NOORLE_DEBUG` is set to anything other than empty, `0`, `false`
+/// or `off`.
+pub fn enabled() -> bool {
+    parse_enabled(std::env::var(DEBUG_VAR).ok().as_deref())
+}
+
+fn parse_enabled(value: Option<&str>) -> bool {
+    value.map(str::trim).is_some_and(|value| {
+        !value.is_empty()
+            && !["0", "false", "off"]
+                .iter()
+                .any(|off| value.eq_ignore_ascii_case(off))
+    })
+}
+
+/// Write one debug line to stderr. Callers check [`enabled`] first.
+pub fn log(source: &str, event: &str, fields: &[(&str, &dyn Display)]) {
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis());
+    eprintln!("{}", format_line(ts, source, event, fields));
+}
+
+fn format_line(ts: u128, source: &str, event: &str, fields: &[(&str, &dyn Display)]) -> String {
+    let mut line = format!("[noorle-debug] ts={} source={} event={}", ts, source, event);
+    for (key, value) in fields {
+        line.push_str(&format!(" {}={}", key, Value(&value.to_string())));
+    }
+    line
+}
+
+/// A field value, quoted when it is empty or contains whitespace, quotes or `=`.
+struct Value<'a>(&'a str);
+
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bare = !self.0.is_empty()
+            && !self
+                .0
+                .chars()
+                .any(|c| c.is_whitespace() || c == '"' || c == '=');
+        if bare {
+            f.write_str(self.0)
+        } else {
+            write!(f, "{:?}", self.0)
+        }
+    }
+}
+
+/// `url` with the values of credential-like query parameters (`appid`,
+/// `apiKey`, ...) replaced, so keys never reach the logs.
+pub fn redact_url(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let params: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((name, _))
+                if SECRET_PARAMS
+                    .iter()
+                    .any(|secret| name.eq_ignore_ascii_case(secret)) =>
+            {
+                format!("{}=REDACTED", name)
+            }
+            _ => param.to_string(),
+        })
+        .collect();
+    format!("{}?{}", base, params.join("&"))
+}
+
+/// Log `event` with `key = value` fields when `NOORLE_DEBUG` is on; `source`
+/// is the calling crate's name.
+///
+/// ```ignore
+/// plugin_http::debug_log!("error", kind = err.kind(), detail = err.detail());
+/// ```
+#[macro_export]
+macro_rules! debug_log {
+    ($event:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        if $crate::debug::enabled() {
+            $crate::debug::log(
+                env!("CARGO_PKG_NAME"),
+                $event,
+                &[$((stringify!($key), &$value as &dyn ::std::fmt::Display)),*],
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_debug_switch() {
+        assert!(!parse_enabled(None));
+        assert!(!parse_enabled(Some("")));
+        assert!(!parse_enabled(Some(" 0 ")));
+        assert!(!parse_enabled(Some("OFF")));
+        assert!(parse_enabled(Some("1")));
+        assert!(parse_enabled(Some("true")));
+    }
+
+    #[test]
+    fn formats_key_value_lines() {
+        let line = format_line(
+            1_000,
+            "weather",
+            "error",
+            &[
+                ("kind", &"parse"),
+                ("detail", &"Failed to parse JSON response"),
+                ("status", &503),
+            ],
+        );
+        assert_eq!(
+            line,
+            "[noorle-debug] ts=1000 source=weather event=error kind=parse detail=\"Failed to parse JSON response\" status=503"
+        );
+        assert_eq!(
+            format_line(0, "news", "retry", &[("reason", &"")]),
+            "[noorle-debug] ts=0 source=news event=retry reason=\"\""
+        );
+    }
+
+    #[test]
+    fn redacts_credentials_in_urls() {
+        assert_eq!(
+            redact_url("https://api.openweathermap.org/data/2.5/weather?q=Austin&appid=secret&units=metric"),
+            "https://api.openweathermap.org/data/2.5/weather?q=Austin&appid=REDACTED&units=metric"
+        );
+        assert_eq!(
+            redact_url("https://newsapi.org/v2/everything?apiKey=abc"),
+            "https://newsapi.org/v2/everything?apiKey=REDACTED"
+        );
+        assert_eq!(
+            redact_url("https://export.arxiv.org/api/query"),
+            "https://export.arxiv.org/api/query"
+        );
+    }
+}
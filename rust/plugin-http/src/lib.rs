@@ -0,0 +1,493 @@
+//! The HTTP GET shared by the Rust example plugins: one place for the
+//! User-Agent, timeout, status check, body read and retry policy.
+
+pub mod debug;
+
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use waki::Client;
+
+pub use waki::Response;
+
+/// Sent unless the caller supplies its own `User-Agent` header.
+pub const USER_AGENT: &str = "Mozilla/5.0 (compatible; noorle/1.0)";
+/// Upper bound on the delay before the first retry when the server gives no
+/// `Retry-After`; doubled for each one after. The actual delay is drawn
+/// uniformly below it (full jitter).
+const BASE_BACKOFF_MS: u64 = 200;
+/// Longest `Retry-After` honored, so a server can't stall a call for minutes.
+const MAX_RETRY_AFTER_SECS: u64 = 30;
+/// Bytes asked for per read by [`read_body_limited`].
+const CHUNK_BYTES: u64 = 64 * 1024;
+
+/// Why a GET did not produce a successful response. `attempts` counts the
+/// requests made before giving up.
+#[derive(Debug, PartialEq)]
+pub enum HttpError {
+    /// The request could not be sent or its body read.
+    Transport { message: String, attempts: u32 },
+    /// The server answered with a non-2xx status. `body` holds its explanation,
+    /// if any.
+    Status {
+        status: u16,
+        body: Vec<u8>,
+        attempts: u32,
+    },
+    /// The body is larger than the caller allowed. `length` is the advertised
+    /// `Content-Length` when the response was rejected before reading.
+    TooLarge { limit: u64, length: Option<u64> },
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Transport { message, attempts } => {
+                write!(f, "{}{}", message, after(*attempts))
+            }
+            HttpError::Status {
+                status, attempts, ..
+            } => write!(f, "HTTP status {}{}", status, after(*attempts)),
+            HttpError::TooLarge {
+                limit,
+                length: Some(length),
+            } => write!(
+                f,
+                "response body is {} bytes, over the {} byte limit",
+                length, limit
+            ),
+            HttpError::TooLarge {
+                limit,
+                length: None,
+            } => write!(f, "response body exceeds the {} byte limit", limit),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// " after N attempts" when the request was retried, otherwise nothing.
+fn after(attempts: u32) -> String {
+    if attempts > 1 {
+        format!(" after {} attempts", attempts)
+    } else {
+        String::new()
+    }
+}
+
+/// GET `url` and return the body of a 2xx response. See [`send_with_retry`]
+/// for the headers, timeout and retry behavior.
+pub fn get_with_retry(
+    url: &str,
+    headers: &[(&'static str, &str)],
+    timeout: Duration,
+    max_retries: u32,
+) -> Result<Vec<u8>, HttpError> {
+    send_with_retry(url, headers, timeout, max_retries)?
+        .body()
+        .map_err(|e| HttpError::Transport {
+            message: format!("Failed to read response body: {}", e),
+            attempts: 1,
+        })
+}
+
+/// GET `url` with `headers` and a connect `timeout`, returning the response
+/// once it has a 2xx status, for callers that need its headers.
+///
+/// Connection failures, 429s and 5xx responses are retried up to
+/// `max_retries` times, waiting for the server's `Retry-After` (in seconds)
+/// when it sends one and otherwise backing off exponentially with full
+/// jitter, seeded once per call. Other statuses are returned at once as
+/// [`HttpError::Status`].
+pub fn send_with_retry(
+    url: &str,
+    headers: &[(&'static str, &str)],
+    timeout: Duration,
+    max_retries: u32,
+) -> Result<Response, HttpError> {
+    let logged_url = debug::redact_url(url);
+    let mut attempts = 0;
+    let attempt = || {
+        attempts += 1;
+        debug_log!("request", url = logged_url, attempt = attempts);
+        let started = Instant::now();
+        let mut request = Client::new().get(url).connect_timeout(timeout);
+        if !headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("User-Agent"))
+        {
+            request = request.header("User-Agent", USER_AGENT);
+        }
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        let sent = request.send();
+        let elapsed_ms = started.elapsed().as_millis();
+        match sent {
+            Err(e) => {
+                let message = format!("HTTP request failed: {}", e);
+                debug_log!(
+                    "transport_error",
+                    url = logged_url,
+                    error = message,
+                    elapsed_ms = elapsed_ms
+                );
+                Outcome::Retry(
+                    HttpError::Transport {
+                        message,
+                        attempts: 1,
+                    },
+                    None,
+                )
+            }
+            Ok(response) => {
+                let status = response.status_code();
+                debug_log!(
+                    "response",
+                    url = logged_url,
+                    status = status,
+                    elapsed_ms = elapsed_ms
+                );
+                if (200..300).contains(&status) {
+                    return Outcome::Done(response);
+                }
+                let retry_after = response
+                    .header("Retry-After")
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                let error = HttpError::Status {
+                    status,
+                    body: response.body().unwrap_or_default(),
+                    attempts: 1,
+                };
+                if is_retryable_status(status) {
+                    Outcome::Retry(error, retry_after)
+                } else {
+                    Outcome::Fail(error)
+                }
+            }
+        }
+    };
+    let sleep = |delay: Duration| {
+        debug_log!("retry", url = logged_url, delay_ms = delay.as_millis());
+        std::thread::sleep(delay);
+    };
+    run(max_retries, Jitter::from_clock(), sleep, attempt)
+}
+
+/// Read `response`'s body, failing with [`HttpError::TooLarge`] once it
+/// passes `max_bytes`. An advertised `Content-Length` over the limit is
+/// rejected before anything is read.
+pub fn read_body_limited(response: Response, max_bytes: u64) -> Result<Vec<u8>, HttpError> {
+    let length = response
+        .header("Content-Length")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    collect_limited(max_bytes, length, || {
+        response.chunk(CHUNK_BYTES).map_err(|e| e.to_string())
+    })
+}
+
+/// Gather chunks from `next` until it returns `None`, enforcing `max_bytes`
+/// against both the advertised `length` and the bytes actually received.
+fn collect_limited(
+    max_bytes: u64,
+    length: Option<u64>,
+    mut next: impl FnMut() -> Result<Option<Vec<u8>>, String>,
+) -> Result<Vec<u8>, HttpError> {
+    if let Some(length) = length.filter(|length| *length > max_bytes) {
+        return Err(HttpError::TooLarge {
+            limit: max_bytes,
+            length: Some(length),
+        });
+    }
+    let mut body = Vec::with_capacity(length.unwrap_or(0) as usize);
+    while let Some(chunk) = next().map_err(|message| HttpError::Transport {
+        message: format!("failed to read response body: {}", message),
+        attempts: 1,
+    })? {
+        body.extend_from_slice(&chunk);
+        if body.len() as u64 > max_bytes {
+            return Err(HttpError::TooLarge {
+                limit: max_bytes,
+                length: None,
+            });
+        }
+    }
+    Ok(body)
+}
+
+/// Server errors and rate limiting are transient; other 4xx responses are not.
+pub fn is_retryable_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+/// The outcome of one request.
+enum Outcome<T> {
+    Done(T),
+    /// A transient failure, with the server's `Retry-After` value if it sent one.
+    Retry(HttpError, Option<String>),
+    /// A failure retrying won't fix.
+    Fail(HttpError),
+}
+
+/// Run `attempt` once plus up to `max_retries` more times while it fails
+/// transiently, sleeping between tries for delays drawn from `jitter`. The
+/// error reports the total number of attempts made.
+fn run<T>(
+    max_retries: u32,
+    mut jitter: Jitter,
+    mut sleep: impl FnMut(Duration),
+    mut attempt: impl FnMut() -> Outcome<T>,
+) -> Result<T, HttpError> {
+    let mut retries = 0;
+    loop {
+        match attempt() {
+            Outcome::Done(value) => return Ok(value),
+            Outcome::Retry(_, retry_after) if retries < max_retries => {
+                retries += 1;
+                sleep(retry_delay(retry_after.as_deref(), retries, &mut jitter));
+            }
+            Outcome::Retry(error, _) | Outcome::Fail(error) => {
+                let attempts = retries + 1;
+                return Err(match error {
+                    HttpError::Transport { message, .. } => {
+                        HttpError::Transport { message, attempts }
+                    }
+                    HttpError::Status { status, body, .. } => HttpError::Status {
+                        status,
+                        body,
+                        attempts,
+                    },
+                    error @ HttpError::TooLarge { .. } => error,
+                });
+            }
+        }
+    }
+}
+
+/// How long to wait before retry number `retry` (1-based): the server's
+/// `Retry-After` when it is a whole number of seconds, otherwise a uniformly
+/// random delay up to 200ms, 400ms, 800ms, ...
+fn retry_delay(retry_after: Option<&str>, retry: u32, jitter: &mut Jitter) -> Duration {
+    match retry_after.and_then(|value| value.trim().parse::<u64>().ok()) {
+        Some(secs) => Duration::from_secs(secs.min(MAX_RETRY_AFTER_SECS)),
+        None => Duration::from_millis(jitter.below(backoff_bound_ms(retry) + 1)),
+    }
+}
+
+/// The ceiling for retry number `retry`'s jittered delay, in milliseconds.
+fn backoff_bound_ms(retry: u32) -> u64 {
+    BASE_BACKOFF_MS << retry.saturating_sub(1).min(16)
+}
+
+/// A small xorshift generator for backoff jitter. Each call to
+/// [`send_with_retry`] seeds its own, so instances that fail together don't
+/// retry together.
+struct Jitter(u64);
+
+impl Jitter {
+    fn new(seed: u64) -> Self {
+        // xorshift never leaves zero, so nudge a zero seed.
+        Jitter(seed.max(1))
+    }
+
+    fn from_clock() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        Jitter::new(nanos ^ (nanos >> 29))
+    }
+
+    /// A value in `0..bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: u16) -> HttpError {
+        HttpError::Status {
+            status,
+            body: Vec::new(),
+            attempts: 1,
+        }
+    }
+
+    #[test]
+    fn retries_transient_failures_with_backoff() {
+        let mut sleeps = Vec::new();
+        let mut calls = 0;
+        let result = run(
+            3,
+            Jitter::new(7),
+            |delay| sleeps.push(delay),
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Outcome::Retry(status(503), None)
+                } else {
+                    Outcome::Done(calls)
+                }
+            },
+        );
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(sleeps.len(), 2);
+        for (retry, delay) in (1..).zip(&sleeps) {
+            assert!(*delay <= Duration::from_millis(backoff_bound_ms(retry)));
+        }
+    }
+
+    #[test]
+    fn jitters_delays_within_each_bound() {
+        for seed in 0..50 {
+            let mut jitter = Jitter::new(seed);
+            for retry in 1..=5 {
+                let delay = retry_delay(None, retry, &mut jitter);
+                assert!(
+                    delay <= Duration::from_millis(backoff_bound_ms(retry)),
+                    "retry {} waited {:?}",
+                    retry,
+                    delay
+                );
+            }
+        }
+
+        let delays: Vec<Duration> = (0..20)
+            .map(|seed| retry_delay(None, 3, &mut Jitter::new(seed)))
+            .collect();
+        assert!(
+            delays.iter().any(|delay| *delay != delays[0]),
+            "different seeds should spread the delays"
+        );
+        assert_eq!(backoff_bound_ms(1), 200);
+        assert_eq!(backoff_bound_ms(3), 800);
+    }
+
+    #[test]
+    fn honors_retry_after_seconds() {
+        let mut sleeps = Vec::new();
+        let result: Result<(), HttpError> = run(
+            2,
+            Jitter::new(7),
+            |delay| sleeps.push(delay),
+            || Outcome::Retry(status(429), Some("3".to_string())),
+        );
+
+        assert_eq!(
+            result,
+            Err(HttpError::Status {
+                status: 429,
+                body: Vec::new(),
+                attempts: 3
+            })
+        );
+        assert_eq!(sleeps, [Duration::from_secs(3), Duration::from_secs(3)]);
+        let mut jitter = Jitter::new(7);
+        assert_eq!(
+            retry_delay(Some("3600"), 1, &mut jitter),
+            Duration::from_secs(MAX_RETRY_AFTER_SECS)
+        );
+        assert!(
+            retry_delay(Some("Wed, 21 Oct 2015 07:28:00 GMT"), 2, &mut jitter)
+                <= Duration::from_millis(400)
+        );
+    }
+
+    #[test]
+    fn stops_at_once_on_permanent_failures() {
+        let mut calls = 0;
+        let result: Result<(), HttpError> = run(
+            5,
+            Jitter::new(7),
+            |_| panic!("no retry expected"),
+            || {
+                calls += 1;
+                Outcome::Fail(status(404))
+            },
+        );
+
+        assert_eq!(result.unwrap_err().to_string(), "HTTP status 404");
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn reports_attempts_and_transport_errors() {
+        let exhausted: Result<(), HttpError> = run(
+            1,
+            Jitter::new(7),
+            |_| {},
+            || Outcome::Retry(status(503), None),
+        );
+        assert_eq!(
+            exhausted.unwrap_err().to_string(),
+            "HTTP status 503 after 2 attempts"
+        );
+
+        let refused = || HttpError::Transport {
+            message: "HTTP request failed: refused".to_string(),
+            attempts: 1,
+        };
+        let transport: Result<(), HttpError> = run(
+            0,
+            Jitter::new(7),
+            |_| {},
+            || Outcome::Retry(refused(), None),
+        );
+        assert_eq!(
+            transport.unwrap_err().to_string(),
+            "HTTP request failed: refused"
+        );
+        let transport: Result<(), HttpError> = run(
+            2,
+            Jitter::new(7),
+            |_| {},
+            || Outcome::Retry(refused(), None),
+        );
+        assert_eq!(
+            transport.unwrap_err().to_string(),
+            "HTTP request failed: refused after 3 attempts"
+        );
+    }
+
+    #[test]
+    fn classifies_retryable_statuses() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(404));
+        assert!(!is_retryable_status(200));
+    }
+
+    #[test]
+    fn limits_body_size() {
+        let chunks = || {
+            let mut chunks = vec![vec![1u8; 4], vec![2u8; 4]].into_iter();
+            move || Ok(chunks.next())
+        };
+        assert_eq!(collect_limited(8, None, chunks()).unwrap().len(), 8);
+        assert_eq!(
+            collect_limited(7, None, chunks()).unwrap_err().to_string(),
+            "response body exceeds the 7 byte limit"
+        );
+        assert_eq!(
+            collect_limited(7, Some(100), || panic!("read before length check"))
+                .unwrap_err()
+                .to_string(),
+            "response body is 100 bytes, over the 7 byte limit"
+        );
+        assert_eq!(
+            collect_limited(7, None, || Err("reset".to_string()))
+                .unwrap_err()
+                .to_string(),
+            "failed to read response body: reset"
+        );
+    }
+}
@@ -0,0 +1,25 @@
+/// OpenWeather's name for an air quality index on its 1 (best) to 5 (worst)
+/// scale. See https://openweathermap.org/api/air-pollution.
+pub fn aqi_label(aqi: u32) -> &'static str {
+    match aqi {
+        1 => "Good",
+        2 => "Fair",
+        3 => "Moderate",
+        4 => "Poor",
+        5 => "Very poor",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_each_index() {
+        let labels: Vec<&str> = (1..=5).map(aqi_label).collect();
+        assert_eq!(labels, ["Good", "Fair", "Moderate", "Poor", "Very poor"]);
+        assert_eq!(aqi_label(0), "Unknown");
+        assert_eq!(aqi_label(6), "Unknown");
+    }
+}
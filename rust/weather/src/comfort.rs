@@ -0,0 +1,126 @@
+use crate::units::{celsius_to_fahrenheit, celsius_to_kelvin, fahrenheit_to_celsius, kelvin_to_celsius};
+use crate::Unit;
+
+/// Apparent ("feels like") temperature blending heat index and wind chill.
+///
+/// Inputs use the response's unit system: °C and m/s for `Metric`, °F and
+/// mph for `Imperial`, K and m/s for `Kelvin`. The result is in the same
+/// temperature unit; Kelvin is computed on the Celsius scale.
+///
+/// - Hot and humid (>= 80°F / 26.7°C, humidity >= 40%): NWS Rothfusz heat index.
+/// - Cold and windy (<= 50°F / 10°C, wind > 3 mph / 4.8 km/h): NWS/Environment
+///   Canada wind chill, using each unit system's own coefficients.
+/// - Otherwise the air temperature is already a fair comfort measure.
+pub fn comfort_index(temperature: f64, humidity: u32, wind_speed: f64, unit: Unit) -> f64 {
+    let humidity = humidity.min(100) as f64;
+
+    match unit {
+        Unit::Metric => {
+            let wind_kmh = wind_speed * 3.6;
+            if temperature >= 26.7 && humidity >= 40.0 {
+                fahrenheit_to_celsius(heat_index_f(celsius_to_fahrenheit(temperature), humidity))
+            } else if temperature <= 10.0 && wind_kmh > 4.8 {
+                let v = wind_kmh.powf(0.16);
+                13.12 + 0.6215 * temperature - 11.37 * v + 0.3965 * temperature * v
+            } else {
+                temperature
+            }
+        }
+        Unit::Imperial => {
+            if temperature >= 80.0 && humidity >= 40.0 {
+                heat_index_f(temperature, humidity)
+            } else if temperature <= 50.0 && wind_speed > 3.0 {
+                let v = wind_speed.powf(0.16);
+                35.74 + 0.6215 * temperature - 35.75 * v + 0.4275 * temperature * v
+            } else {
+                temperature
+            }
+        }
+        Unit::Kelvin => celsius_to_kelvin(comfort_index(
+            kelvin_to_celsius(temperature),
+            humidity as u32,
+            wind_speed,
+            Unit::Metric,
+        )),
+    }
+}
+
+/// Human-readable band for a comfort index expressed in `unit`.
+pub fn comfort_label(index: f64, unit: Unit) -> &'static str {
+    let celsius = match unit {
+        Unit::Metric => index,
+        Unit::Imperial => fahrenheit_to_celsius(index),
+        Unit::Kelvin => kelvin_to_celsius(index),
+    };
+
+    match celsius {
+        c if c < -10.0 => "Dangerously cold",
+        c if c < 0.0 => "Very cold",
+        c if c < 10.0 => "Cold",
+        c if c < 18.0 => "Cool",
+        c if c < 24.0 => "Comfortable",
+        c if c < 29.0 => "Warm",
+        c if c < 35.0 => "Hot",
+        _ => "Dangerously hot",
+    }
+}
+
+/// Rothfusz regression used by the US National Weather Service (°F in, °F out).
+fn heat_index_f(t: f64, rh: f64) -> f64 {
+    -42.379 + 2.04901523 * t + 10.14333127 * rh
+        - 0.22475541 * t * rh
+        - 0.00683783 * t * t
+        - 0.05481717 * rh * rh
+        + 0.00122874 * t * t * rh
+        + 0.00085282 * t * rh * rh
+        - 0.00000199 * t * t * rh * rh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mild_conditions_use_air_temperature() {
+        assert_eq!(comfort_index(21.0, 50, 2.0, Unit::Metric), 21.0);
+        assert_eq!(comfort_index(70.0, 50, 5.0, Unit::Imperial), 70.0);
+    }
+
+    #[test]
+    fn hot_humid_conditions_use_heat_index() {
+        // NWS table: 90°F at 70% humidity feels like ~106°F.
+        let imperial = comfort_index(90.0, 70, 5.0, Unit::Imperial);
+        assert!((imperial - 105.9).abs() < 0.5, "{}", imperial);
+
+        let metric = comfort_index(fahrenheit_to_celsius(90.0), 70, 2.0, Unit::Metric);
+        assert!((metric - fahrenheit_to_celsius(imperial)).abs() < 0.01);
+    }
+
+    #[test]
+    fn cold_windy_conditions_use_wind_chill() {
+        // NWS table: 0°F with 15 mph wind feels like ~-19°F.
+        let imperial = comfort_index(0.0, 60, 15.0, Unit::Imperial);
+        assert!((imperial - -19.4).abs() < 0.5, "{}", imperial);
+
+        // Environment Canada table: -10°C with 20 km/h wind feels like ~-18°C.
+        let metric = comfort_index(-10.0, 60, 20.0 / 3.6, Unit::Metric);
+        assert!((metric - -17.9).abs() < 0.5, "{}", metric);
+    }
+
+    #[test]
+    fn labels_bands_consistently_across_units() {
+        assert_eq!(comfort_label(21.0, Unit::Metric), "Comfortable");
+        assert_eq!(comfort_label(70.0, Unit::Imperial), "Comfortable");
+        assert_eq!(comfort_label(-19.4, Unit::Imperial), "Dangerously cold");
+        assert_eq!(comfort_label(40.0, Unit::Metric), "Dangerously hot");
+        assert_eq!(comfort_label(294.15, Unit::Kelvin), "Comfortable");
+    }
+
+    #[test]
+    fn computes_kelvin_on_the_celsius_scale() {
+        let metric = comfort_index(-10.0, 60, 20.0 / 3.6, Unit::Metric);
+        let kelvin = comfort_index(263.15, 60, 20.0 / 3.6, Unit::Kelvin);
+        assert!((kelvin - celsius_to_kelvin(metric)).abs() < 1e-9, "{}", kelvin);
+        assert_eq!(comfort_index(294.15, 50, 2.0, Unit::Kelvin), 294.15);
+    }
+}
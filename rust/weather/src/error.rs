@@ -0,0 +1,235 @@
+use plugin_http::HttpError;
+use serde::Deserialize;
+use std::fmt;
+
+/// What went wrong, as reported across the WIT boundary. The error string is
+/// `"<kind>: <detail>"` with a stable kind prefix, so callers can decide
+/// whether to retry or surface the message without parsing the detail.
+#[derive(Debug, PartialEq)]
+pub enum PluginError {
+    /// OpenWeather could not be reached or the response could not be read.
+    Network(String),
+    /// OpenWeather answered with a non-2xx status not covered below.
+    HttpStatus(u16, String),
+    /// The response body was not the JSON we expected.
+    Parse(String),
+    /// OpenWeather rejected the call for exceeding the key's rate limit.
+    RateLimited(String),
+    /// An argument or configuration value was rejected before any request.
+    InvalidInput(String),
+    /// The requested location or data does not exist.
+    NotFound(String),
+    /// The observation is older than the caller's staleness limit.
+    Stale(String),
+}
+
+impl PluginError {
+    /// The stable prefix of the WIT error string.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PluginError::Network(_) => "network",
+            PluginError::HttpStatus(..) => "http_status",
+            PluginError::Parse(_) => "parse",
+            PluginError::RateLimited(_) => "rate_limited",
+            PluginError::InvalidInput(_) => "invalid_input",
+            PluginError::NotFound(_) => "not_found",
+            PluginError::Stale(_) => "stale",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match self {
+            PluginError::Network(detail)
+            | PluginError::HttpStatus(_, detail)
+            | PluginError::Parse(detail)
+            | PluginError::RateLimited(detail)
+            | PluginError::InvalidInput(detail)
+            | PluginError::NotFound(detail)
+            | PluginError::Stale(detail) => detail,
+        }
+    }
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.detail())
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// Classify an error from an internal function. Errors raised without a kind
+/// are argument checks, so they are reported as `invalid_input`.
+impl From<anyhow::Error> for PluginError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<PluginError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let detail = err.to_string();
+        for cause in err.chain() {
+            if let Some(weather) = cause.downcast_ref::<WeatherError>() {
+                return match weather {
+                    WeatherError::CityNotFound(_) => PluginError::NotFound(detail),
+                    WeatherError::Upstream { status: 429, .. } => PluginError::RateLimited(detail),
+                    WeatherError::Upstream { status, .. } => PluginError::HttpStatus(*status, detail),
+                    WeatherError::StaleData { .. } => PluginError::Stale(detail),
+                };
+            }
+            if let Some(HttpError::Transport { .. }) = cause.downcast_ref::<HttpError>() {
+                return PluginError::Network(detail);
+            }
+        }
+        PluginError::InvalidInput(detail)
+    }
+}
+
+/// The WIT error string for an error from an internal function.
+pub fn to_wit(err: anyhow::Error) -> String {
+    report(PluginError::from(err))
+}
+
+/// The WIT error string for one failed part of a combined call, with `what`
+/// leading the detail, e.g. `"rate_limited: Air quality: ..."`.
+pub fn to_wit_part(err: anyhow::Error, what: &str) -> String {
+    let err = PluginError::from(err);
+    let detail = format!("{}: {}", what, err.detail());
+    plugin_http::debug_log!("error", kind = err.kind(), detail = detail);
+    format!("{}: {}", err.kind(), detail)
+}
+
+/// The WIT error string for `err`, logged first when `NOORLE_DEBUG` is on.
+pub fn report(err: PluginError) -> String {
+    plugin_http::debug_log!("error", kind = err.kind(), detail = err.detail());
+    err.to_string()
+}
+
+/// Failures reported by, or about the data from, the OpenWeather API.
+#[derive(Debug, PartialEq)]
+pub enum WeatherError {
+    /// OpenWeather could not resolve the requested location (HTTP 404).
+    CityNotFound(String),
+    /// Any other non-2xx response, with the upstream message when one was sent.
+    Upstream { status: u16, message: Option<String> },
+    /// The observation is older than the caller's staleness limit, or carries
+    /// no timestamp to check against (`age_secs` is `None`).
+    StaleData { age_secs: Option<u64>, max_secs: u64 },
+}
+
+impl fmt::Display for WeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeatherError::CityNotFound(message) => write!(f, "City not found: {}", message),
+            WeatherError::Upstream { status, message: Some(message) } => {
+                write!(f, "HTTP error: status code {}: {}", status, message)
+            }
+            WeatherError::Upstream { status, message: None } => {
+                write!(f, "HTTP error: status code {}", status)
+            }
+            WeatherError::StaleData { age_secs: Some(age), max_secs } => {
+                write!(f, "Stale data: observation is {}s old (max {}s)", age, max_secs)
+            }
+            WeatherError::StaleData { age_secs: None, max_secs } => {
+                write!(f, "Stale data: observation time not reported (max {}s)", max_secs)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WeatherError {}
+
+/// Error body OpenWeather sends with non-2xx responses,
+/// e.g. `{"cod":"404","message":"city not found"}`.
+#[derive(Deserialize)]
+struct OpenWeatherErrorBody {
+    message: Option<String>,
+}
+
+/// Classify a non-2xx response using its status and (possibly non-JSON) body.
+pub fn from_response(status: u16, body: &[u8]) -> WeatherError {
+    let message = serde_json::from_slice::<OpenWeatherErrorBody>(body)
+        .ok()
+        .and_then(|b| b.message)
+        .filter(|m| !m.trim().is_empty());
+
+    match (status, message) {
+        (404, message) => WeatherError::CityNotFound(message.unwrap_or_else(|| "city not found".to_string())),
+        (status, message) => WeatherError::Upstream { status, message },
+    }
+}
+
+/// Fail with `StaleData` when the observation at `observed_at` is more than
+/// `max_secs` older than `now` (both Unix seconds).
+pub fn check_staleness(observed_at: Option<u64>, now: u64, max_secs: u64) -> Result<(), WeatherError> {
+    match observed_at {
+        Some(observed_at) => {
+            // Clock skew can put the observation slightly in the future; treat that as fresh.
+            let age = now.saturating_sub(observed_at);
+            if age > max_secs {
+                Err(WeatherError::StaleData { age_secs: Some(age), max_secs })
+            } else {
+                Ok(())
+            }
+        }
+        None => Err(WeatherError::StaleData { age_secs: None, max_secs }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_wit_errors_with_a_stable_kind() {
+        let not_found = anyhow::Error::from(from_response(404, br#"{"cod":"404","message":"city not found"}"#));
+        assert_eq!(to_wit(not_found), "not_found: City not found: city not found");
+
+        let rate_limited = anyhow::Error::from(from_response(429, b""));
+        assert_eq!(to_wit(rate_limited), "rate_limited: HTTP error: status code 429");
+
+        let upstream = anyhow::Error::from(from_response(502, b""));
+        assert_eq!(PluginError::from(upstream), PluginError::HttpStatus(502, "HTTP error: status code 502".to_string()));
+
+        let transport = anyhow::Error::from(HttpError::Transport { message: "connection refused".to_string(), attempts: 1 });
+        assert_eq!(to_wit(transport), "network: connection refused");
+
+        let parse = anyhow::Error::from(PluginError::Parse("Failed to parse JSON response: EOF".to_string()));
+        assert_eq!(to_wit(parse), "parse: Failed to parse JSON response: EOF");
+
+        assert_eq!(to_wit(anyhow::Error::msg("Location cannot be empty")), "invalid_input: Location cannot be empty");
+
+        let part = anyhow::Error::from(from_response(429, b""));
+        assert_eq!(to_wit_part(part, "Air quality"), "rate_limited: Air quality: HTTP error: status code 429");
+    }
+
+    #[test]
+    fn maps_404_body_to_city_not_found() {
+        let err = from_response(404, br#"{"cod":"404","message":"city not found"}"#);
+        assert_eq!(err, WeatherError::CityNotFound("city not found".to_string()));
+        assert_eq!(err.to_string(), "City not found: city not found");
+    }
+
+    #[test]
+    fn surfaces_upstream_message_for_other_statuses() {
+        let err = from_response(401, br#"{"cod":401,"message":"Invalid API key."}"#);
+        assert_eq!(err.to_string(), "HTTP error: status code 401: Invalid API key.");
+    }
+
+    #[test]
+    fn tolerates_unparseable_bodies() {
+        assert_eq!(from_response(502, b"<html>Bad Gateway</html>").to_string(), "HTTP error: status code 502");
+        assert_eq!(from_response(404, b"").to_string(), "City not found: city not found");
+    }
+
+    #[test]
+    fn flags_observations_past_the_staleness_limit() {
+        assert_eq!(check_staleness(Some(1_000), 2_800, 1_800), Ok(()));
+        assert_eq!(check_staleness(Some(1_100), 1_000, 1_800), Ok(()));
+
+        let err = check_staleness(Some(1_000), 2_801, 1_800).unwrap_err();
+        assert_eq!(err.to_string(), "Stale data: observation is 1801s old (max 1800s)");
+
+        let err = check_staleness(None, 2_800, 1_800).unwrap_err();
+        assert_eq!(err, WeatherError::StaleData { age_secs: None, max_secs: 1_800 });
+    }
+}
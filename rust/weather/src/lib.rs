@@ -1,83 +1,926 @@
 #![allow(unsafe_op_in_unsafe_fn)]
 
+mod air;
+mod comfort;
+mod error;
+mod locale;
 mod types;
+mod units;
 
-wit_bindgen::generate!({
-    world: "weather-component",
-    path: "./wit",
-});
+mod bindings {
+    // The generated export shims take one argument per flattened WIT parameter.
+    #![allow(clippy::too_many_arguments)]
+
+    wit_bindgen::generate!({
+        world: "weather-component",
+        path: "./wit",
+    });
+}
+
+use bindings::*;
 
 use anyhow::{Error, Result};
-use std::time::Duration;
-use types::{OpenWeatherResponse, WeatherParams};
-use waki::Client;
+use error::PluginError;
+use plugin_http::HttpError;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use types::{
+    OpenWeatherAirQualityResponse, OpenWeatherForecastEntry, OpenWeatherForecastResponse, OpenWeatherGeoCandidate,
+    OpenWeatherResponse, OpenWeatherWeather, Place, WeatherParams,
+};
 
 const OPENWEATHER_ENDPOINT: &str = "https://api.openweathermap.org/data/2.5/weather";
-const TIMEOUT_SECS: u64 = 10;
+const FORECAST_ENDPOINT: &str = "https://api.openweathermap.org/data/2.5/forecast";
+const AIR_POLLUTION_ENDPOINT: &str = "https://api.openweathermap.org/data/2.5/air_pollution";
+const GEOCODING_ENDPOINT: &str = "https://api.openweathermap.org/geo/1.0/direct";
+/// The most candidates OpenWeather's geocoder returns for one name.
+const MAX_GEOCODE_RESULTS: u32 = 5;
+/// Environment variable overriding the request timeout in seconds.
+const TIMEOUT_VAR: &str = "OPENWEATHER_TIMEOUT_SECS";
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+/// Most locations `check-weather-multi` looks up in one call, each a separate request.
+const MAX_BATCH_LOCATIONS: usize = 20;
+/// Length of one forecast timeslot.
+const FORECAST_SLOT_SECS: u64 = 3 * 60 * 60;
+/// How far ahead of its first timeslot the overview's forecast summary looks.
+const OVERVIEW_WINDOW_SECS: u64 = 24 * 60 * 60;
+/// Longest location accepted; real place names with qualifiers are far shorter.
+const MAX_LOCATION_CHARS: usize = 200;
+const API_KEY_VAR: &str = "OPENWEATHER_API_KEY";
 
 fn get_weather(api_key: &str, params: WeatherParams) -> Result<WeatherResponse, Error> {
-    let unit_query = match params.unit {
-        Unit::Metric => "metric",
-        Unit::Imperial => "imperial",
-    };
+    let mut request_url = format!(
+        "{}?{}&appid={}&units={}",
+        OPENWEATHER_ENDPOINT, place_param(&params.place)?, api_key, units_param(params.unit)
+    );
+    if let Some(lang) = &params.lang {
+        request_url.push_str(&format!("&lang={}", lang));
+    }
+
+    let (body_bytes, elapsed_ms) = fetch(&request_url)?;
+
+    let open_weather_response: OpenWeatherResponse = serde_json::from_slice(&body_bytes)
+        .map_err(|e| PluginError::Parse(format!("Failed to parse JSON response: {}", e)))?;
+
+    if let Some(max_secs) = params.max_staleness_secs {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::msg(format!("System clock is before the Unix epoch: {}", e)))?
+            .as_secs();
+        error::check_staleness(open_weather_response.dt, now, max_secs)?;
+    }
+
+    let mut weather = map_weather(open_weather_response, params.unit);
+    let descriptions: Vec<String> = weather
+        .weather_conditions
+        .iter()
+        .map(|condition| condition.description.clone())
+        .collect();
+    weather.localized = locale::is_localized(params.lang.as_deref(), &descriptions);
+    weather.elapsed_ms = params.with_metrics.then_some(elapsed_ms);
+    Ok(weather)
+}
+
+/// Look up each location in turn with one API key, recording failures per
+/// location so one bad name doesn't fail the rest.
+fn get_weather_multi(api_key: &str, locations: Vec<String>, unit: Unit) -> Result<Vec<LocationWeather>, Error> {
+    if locations.is_empty() {
+        return Err(PluginError::InvalidInput("At least one location is required".to_string()).into());
+    }
+    if locations.len() > MAX_BATCH_LOCATIONS {
+        return Err(PluginError::InvalidInput(format!(
+            "At most {} locations can be checked at once, got {}",
+            MAX_BATCH_LOCATIONS,
+            locations.len()
+        ))
+        .into());
+    }
 
-    let encoded_location = urlencoding::encode(&params.location);
+    Ok(locations
+        .into_iter()
+        .map(|location| {
+            let params = WeatherParams {
+                place: Place::Name { location: location.clone(), country: String::new() },
+                unit,
+                lang: None,
+                max_staleness_secs: None,
+                with_metrics: false,
+            };
+            LocationWeather {
+                location,
+                weather: get_weather(api_key, params).map_err(error::to_wit),
+            }
+        })
+        .collect())
+}
 
+/// Fetch the 5-day forecast for a place as OpenWeather's 3-hour timeslots.
+fn get_forecast(api_key: &str, place: &Place, unit: Unit) -> Result<Vec<ForecastEntry>, Error> {
     let request_url = format!(
-        "{}?q={}&appid={}&units={}",
-        OPENWEATHER_ENDPOINT, encoded_location, api_key, unit_query
+        "{}?{}&appid={}&units={}",
+        FORECAST_ENDPOINT,
+        place_param(place)?,
+        api_key,
+        units_param(unit)
     );
 
-    let response = Client::new()
-        .get(&request_url)
-        .connect_timeout(Duration::from_secs(TIMEOUT_SECS))
-        .header("User-Agent", "Mozilla/5.0 (compatible; noorle/1.0)")
-        .send()
-        .map_err(|e| Error::msg(format!("HTTP request failed: {}", e)))?;
+    let (body_bytes, _) = fetch(&request_url)?;
+    let forecast: OpenWeatherForecastResponse = serde_json::from_slice(&body_bytes)
+        .map_err(|e| PluginError::Parse(format!("Failed to parse JSON response: {}", e)))?;
+
+    Ok(forecast.list.into_iter().map(map_forecast_entry).collect())
+}
+
+/// Geocode `location` once, then fetch current weather, the forecast and air
+/// quality for the best match's coordinates, one request after another. Only
+/// a failure to resolve the location fails the call; a part that fails is left
+/// out and its error recorded.
+fn get_weather_overview(api_key: &str, location: &str, unit: Unit) -> Result<WeatherOverview, Error> {
+    let place = geocode(api_key, location)?.into_iter().next().ok_or_else(|| {
+        PluginError::NotFound(format!("No location found matching '{}'", location.trim()))
+    })?;
+    let coords = || Place::Coords { lat: place.lat, lon: place.lon };
+    let mut errors = Vec::new();
 
-    let status = response.status_code();
-    if !(200..300).contains(&status) {
-        return Err(Error::msg(format!("HTTP error: status code {}", status)));
+    let current = get_weather(
+        api_key,
+        WeatherParams {
+            place: coords(),
+            unit,
+            lang: None,
+            max_staleness_secs: None,
+            with_metrics: false,
+        },
+    );
+    let current = overview_part(current, "Current weather", &mut errors);
+
+    let forecast = get_forecast(api_key, &coords(), unit).and_then(|entries| {
+        summarize_forecast(&entries)
+            .ok_or_else(|| PluginError::NotFound("No forecast timeslots returned".to_string()).into())
+    });
+    let forecast = overview_part(forecast, "Forecast", &mut errors);
+
+    let air_quality = get_air_quality(api_key, place.lat, place.lon);
+    let air_quality = overview_part(air_quality, "Air quality", &mut errors);
+
+    Ok(WeatherOverview {
+        place,
+        current,
+        forecast,
+        air_quality,
+        errors,
+    })
+}
+
+/// The value of one overview part, or `None` with its error appended to `errors`.
+fn overview_part<T>(result: Result<T, Error>, what: &str, errors: &mut Vec<String>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            errors.push(error::to_wit_part(e, what));
+            None
+        }
     }
+}
 
-    let body_bytes = response.body()
-        .map_err(|e| Error::msg(format!("Failed to read response body: {}", e)))?;
+/// Summarize the timeslots within a day of the first one: their time span,
+/// temperature range and most common condition. `None` for an empty forecast.
+fn summarize_forecast(entries: &[ForecastEntry]) -> Option<ForecastSummary> {
+    let first = entries.first()?;
+    let window: Vec<&ForecastEntry> = entries
+        .iter()
+        .take_while(|entry| entry.timestamp < first.timestamp + OVERVIEW_WINDOW_SECS)
+        .collect();
+    let last = window.last()?;
 
-    let open_weather_response: OpenWeatherResponse = serde_json::from_slice(&body_bytes)
-        .map_err(|e| Error::msg(format!("Failed to parse JSON response: {}", e)))?;
-
-    let weather_response = WeatherResponse {
-        location: open_weather_response.name,
-        temperature: open_weather_response.main.temp,
-        feels_like_temperature: open_weather_response.main.feels_like,
-        wind_speed: Some(open_weather_response.wind.speed),
-        wind_degrees: Some(open_weather_response.wind.deg as u32),
-        humidity: Some(open_weather_response.main.humidity as u32),
-        unit: params.unit,
-        weather_conditions: open_weather_response.weather.into_iter().map(|w| w.description).collect(),
+    let mut counts: Vec<(&WeatherCondition, usize)> = Vec::new();
+    for condition in window.iter().filter_map(|entry| entry.weather_conditions.first()) {
+        match counts.iter_mut().find(|(counted, _)| counted.id == condition.id) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((condition, 1)),
+        }
+    }
+    // `max_by_key` keeps the last of equal counts, so search from the end to
+    // prefer the condition seen first.
+    let condition = counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(condition, _)| (*condition).clone());
+
+    Some(ForecastSummary {
+        starts_at: first.timestamp,
+        ends_at: last.timestamp + FORECAST_SLOT_SECS,
+        temperature_min: window.iter().map(|entry| entry.temperature).fold(f64::INFINITY, f64::min),
+        temperature_max: window.iter().map(|entry| entry.temperature).fold(f64::NEG_INFINITY, f64::max),
+        condition,
+    })
+}
+
+/// Fetch the current air quality index and pollutant concentrations at a point.
+fn get_air_quality(api_key: &str, lat: f64, lon: f64) -> Result<AirQualityResponse, Error> {
+    validate_coordinates(lat, lon)?;
+    let request_url = format!("{}?lat={}&lon={}&appid={}", AIR_POLLUTION_ENDPOINT, lat, lon, api_key);
+
+    let (body_bytes, _) = fetch(&request_url)?;
+    let air: OpenWeatherAirQualityResponse = serde_json::from_slice(&body_bytes)
+        .map_err(|e| PluginError::Parse(format!("Failed to parse JSON response: {}", e)))?;
+
+    map_air_quality(air)
+}
+
+/// Map the first (current) reading of an air pollution payload onto the WIT record.
+fn map_air_quality(resp: OpenWeatherAirQualityResponse) -> Result<AirQualityResponse, Error> {
+    let reading = resp
+        .list
+        .into_iter()
+        .next()
+        .ok_or_else(|| PluginError::NotFound("No air quality data returned for this location".to_string()))?;
+
+    let components = reading.components;
+    Ok(AirQualityResponse {
+        aqi: reading.main.aqi,
+        label: air::aqi_label(reading.main.aqi).to_string(),
+        components: AirComponents {
+            co: components.co,
+            no: components.no,
+            no2: components.no2,
+            o3: components.o3,
+            so2: components.so2,
+            pm25: components.pm2_5,
+            pm10: components.pm10,
+            nh3: components.nh3,
+        },
+        measured_at: reading.dt,
+    })
+}
+
+/// Resolve a place name to the locations it could mean, best match first.
+fn geocode(api_key: &str, location: &str) -> Result<Vec<GeoCandidate>, Error> {
+    let location = normalize_location(location, "")?;
+    let request_url = format!(
+        "{}?q={}&limit={}&appid={}",
+        GEOCODING_ENDPOINT,
+        urlencoding::encode(&location),
+        MAX_GEOCODE_RESULTS,
+        api_key
+    );
+
+    let (body_bytes, _) = fetch(&request_url)?;
+    let candidates: Vec<OpenWeatherGeoCandidate> = serde_json::from_slice(&body_bytes)
+        .map_err(|e| PluginError::Parse(format!("Failed to parse JSON response: {}", e)))?;
+
+    Ok(candidates.into_iter().map(map_geo_candidate).collect())
+}
+
+/// GET an OpenWeather URL, returning the body of a 2xx response and how long
+/// the request took in milliseconds.
+fn fetch(request_url: &str) -> Result<(Vec<u8>, u32), Error> {
+    let started = Instant::now();
+    // waki 0.5 only exposes the connect timeout; it has no setting for the
+    // first-byte or between-bytes timeouts of a slow response.
+    let body_bytes = plugin_http::get_with_retry(request_url, &[], timeout(), 0).map_err(|e| match e {
+        HttpError::Status { status, body, .. } => Error::from(error::from_response(status, &body)),
+        e => Error::from(e),
+    })?;
+    Ok((body_bytes, elapsed_ms(started)))
+}
+
+/// OpenWeather's `units` value for a temperature unit.
+fn units_param(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Metric => "metric",
+        Unit::Imperial => "imperial",
+        Unit::Kelvin => "standard",
+    }
+}
+
+/// The OpenWeather API key from the environment.
+fn api_key() -> Result<String, PluginError> {
+    let api_key = std::env::var(API_KEY_VAR)
+        .unwrap_or_else(|_| String::from(""));
+
+    if api_key.is_empty() {
+        return Err(PluginError::InvalidInput(format!("{} environment variable not set", API_KEY_VAR)));
+    }
+    Ok(api_key)
+}
+
+/// Request timeout from the environment, falling back to the default when
+/// unset, unparseable or zero.
+fn timeout() -> Duration {
+    Duration::from_secs(parse_timeout_secs(std::env::var(TIMEOUT_VAR).ok().as_deref()))
+}
+
+fn parse_timeout_secs(value: Option<&str>) -> u64 {
+    value
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|&secs| secs > 0)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS)
+}
+
+/// Milliseconds since `started`, saturating at `u32::MAX`.
+fn elapsed_ms(started: Instant) -> u32 {
+    u32::try_from(started.elapsed().as_millis()).unwrap_or(u32::MAX)
+}
+
+/// The query parameters that select `place`: `q=` for a name, `lat=&lon=`
+/// for coordinates.
+fn place_param(place: &Place) -> Result<String, Error> {
+    match place {
+        Place::Name { location, country } => {
+            let location = normalize_location(location, country)?;
+            Ok(format!("q={}", urlencoding::encode(&location)))
+        }
+        Place::Coords { lat, lon } => {
+            validate_coordinates(*lat, *lon)?;
+            Ok(format!("lat={}&lon={}", lat, lon))
+        }
+    }
+}
+
+/// Reject latitudes outside [-90, 90] and longitudes outside [-180, 180].
+fn validate_coordinates(lat: f64, lon: f64) -> Result<(), Error> {
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(PluginError::InvalidInput(format!(
+            "Invalid latitude {}: expected a value between -90 and 90",
+            lat
+        ))
+        .into());
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(PluginError::InvalidInput(format!(
+            "Invalid longitude {}: expected a value between -180 and 180",
+            lon
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Clean up a user-supplied location before it is sent as `q=`.
+///
+/// Trims the input, collapses runs of whitespace, tidies the spacing around
+/// comma-separated qualifiers ("Paris , fr" -> "Paris,fr") and, when
+/// `country` is non-empty, appends it as a two-letter ISO 3166 code. Empty and
+/// overly long locations are rejected before any request is made.
+fn normalize_location(location: &str, country: &str) -> Result<String, Error> {
+    let location = location
+        .split(',')
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    if location.is_empty() {
+        return Err(PluginError::InvalidInput("Location cannot be empty".to_string()).into());
+    }
+    if location.chars().count() > MAX_LOCATION_CHARS {
+        return Err(PluginError::InvalidInput(format!(
+            "Location is too long (max {} characters)",
+            MAX_LOCATION_CHARS
+        ))
+        .into());
+    }
+
+    let country = country.trim();
+    if country.is_empty() {
+        return Ok(location);
+    }
+
+    if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(PluginError::InvalidInput(format!(
+            "Invalid country code '{}': expected a two-letter ISO 3166 code",
+            country
+        ))
+        .into());
+    }
+
+    if location.contains(',') {
+        return Err(PluginError::InvalidInput(format!(
+            "Location '{}' already includes a country qualifier; omit the separate country code",
+            location
+        ))
+        .into());
+    }
+
+    Ok(format!("{},{}", location, country.to_ascii_uppercase()))
+}
+
+/// Map a raw OpenWeather payload onto the WIT response record.
+fn map_weather(resp: OpenWeatherResponse, unit: Unit) -> WeatherResponse {
+    let wind_speed = resp.wind.as_ref().and_then(|w| w.speed);
+    let humidity = resp.main.humidity.map(|humidity| humidity as u32);
+
+    let comfort_index = match (humidity, wind_speed) {
+        (Some(humidity), Some(wind_speed)) => {
+            Some(comfort::comfort_index(resp.main.temp, humidity, wind_speed, unit))
+        }
+        _ => None,
     };
 
-    Ok(weather_response)
+    WeatherResponse {
+        location: resp.name,
+        temperature: resp.main.temp,
+        feels_like_temperature: resp.main.feels_like,
+        wind_speed,
+        wind_degrees: resp.wind.as_ref().and_then(|w| w.deg).map(|deg| deg as u32),
+        humidity,
+        pressure: resp.main.pressure.map(|pressure| pressure.round() as u32),
+        cloud_cover: resp.clouds.as_ref().map(|clouds| clouds.all as u32),
+        visibility_meters: resp.visibility.map(|visibility| visibility as u32),
+        unit,
+        weather_conditions: resp.weather.into_iter().map(map_condition).collect(),
+        comfort_index,
+        comfort_label: comfort_index.map(|index| comfort::comfort_label(index, unit).to_string()),
+        observed_at: resp.dt,
+        sunrise: resp.sys.as_ref().and_then(|sys| sys.sunrise),
+        sunset: resp.sys.as_ref().and_then(|sys| sys.sunset),
+        localized: None,
+        elapsed_ms: None,
+    }
+}
+
+fn map_condition(weather: OpenWeatherWeather) -> WeatherCondition {
+    WeatherCondition {
+        id: weather.id,
+        main: weather.main,
+        description: weather.description,
+        icon: weather.icon,
+    }
+}
+
+/// Map one raw forecast timeslot onto the WIT record.
+fn map_forecast_entry(entry: OpenWeatherForecastEntry) -> ForecastEntry {
+    ForecastEntry {
+        timestamp: entry.dt,
+        temperature: entry.main.temp,
+        feels_like_temperature: entry.main.feels_like,
+        wind_speed: entry.wind.as_ref().and_then(|w| w.speed),
+        wind_degrees: entry.wind.as_ref().and_then(|w| w.deg).map(|deg| deg as u32),
+        humidity: entry.main.humidity.map(|humidity| humidity as u32),
+        weather_conditions: entry.weather.into_iter().map(map_condition).collect(),
+    }
+}
+
+fn map_geo_candidate(candidate: OpenWeatherGeoCandidate) -> GeoCandidate {
+    GeoCandidate {
+        name: candidate.name,
+        country: candidate.country,
+        state: candidate.state.filter(|state| !state.trim().is_empty()),
+        lat: candidate.lat,
+        lon: candidate.lon,
+    }
+}
+
+fn required_config() -> Vec<ConfigVar> {
+    vec![
+        ConfigVar {
+            name: API_KEY_VAR.to_string(),
+            required: true,
+            description: "OpenWeatherMap API key, from https://openweathermap.org/api".to_string(),
+        },
+        ConfigVar {
+            name: TIMEOUT_VAR.to_string(),
+            required: false,
+            description: format!(
+                "Connect timeout for OpenWeather requests in seconds (default {})",
+                DEFAULT_TIMEOUT_SECS
+            ),
+        },
+        ConfigVar {
+            name: plugin_http::debug::DEBUG_VAR.to_string(),
+            required: false,
+            description: "Set to 1 to log requests, responses, retries and errors to stderr".to_string(),
+        },
+    ]
 }
 
 struct WeatherComponent;
 
 impl Guest for WeatherComponent {
-    fn check_weather(location: String, unit: Unit) -> Result<WeatherResponse, String> {
-        let api_key = std::env::var("OPENWEATHER_API_KEY")
-            .unwrap_or_else(|_| String::from(""));
+    fn check_weather(
+        location: String,
+        country: String,
+        unit: Unit,
+        options: WeatherOptions,
+    ) -> Result<WeatherResponse, String> {
+        let api_key = api_key().map_err(error::report)?;
 
-        if api_key.is_empty() {
-            return Err("OPENWEATHER_API_KEY environment variable not set".to_string());
-        }
+        let params = WeatherParams {
+            place: Place::Name { location, country },
+            unit,
+            lang: locale::normalize_lang(options.lang.as_deref().unwrap_or_default()).map_err(error::report)?,
+            max_staleness_secs: options.max_staleness_secs.map(u64::from),
+            with_metrics: options.with_metrics,
+        };
+
+        get_weather(&api_key, params).map_err(error::to_wit)
+    }
+
+    fn check_weather_coords(lat: f64, lon: f64, unit: Unit) -> Result<WeatherResponse, String> {
+        let api_key = api_key().map_err(error::report)?;
 
         let params = WeatherParams {
-            location,
+            place: Place::Coords { lat, lon },
             unit,
+            lang: None,
+            max_staleness_secs: None,
+            with_metrics: false,
         };
 
-        get_weather(&api_key, params).map_err(|e| e.to_string())
+        get_weather(&api_key, params).map_err(error::to_wit)
+    }
+
+    fn check_weather_multi(locations: Vec<String>, unit: Unit) -> Result<Vec<LocationWeather>, String> {
+        let api_key = api_key().map_err(error::report)?;
+        get_weather_multi(&api_key, locations, unit).map_err(error::to_wit)
+    }
+
+    fn get_forecast(location: String, unit: Unit) -> Result<Vec<ForecastEntry>, String> {
+        let api_key = api_key().map_err(error::report)?;
+        let place = Place::Name { location, country: String::new() };
+        get_forecast(&api_key, &place, unit).map_err(error::to_wit)
+    }
+
+    fn get_weather_overview(location: String, unit: Unit) -> Result<WeatherOverview, String> {
+        let api_key = api_key().map_err(error::report)?;
+        get_weather_overview(&api_key, &location, unit).map_err(error::to_wit)
+    }
+
+    fn get_air_quality(lat: f64, lon: f64) -> Result<AirQualityResponse, String> {
+        let api_key = api_key().map_err(error::report)?;
+        get_air_quality(&api_key, lat, lon).map_err(error::to_wit)
+    }
+
+    fn geocode(location: String) -> Result<Vec<GeoCandidate>, String> {
+        let api_key = api_key().map_err(error::report)?;
+        geocode(&api_key, &location).map_err(error::to_wit)
+    }
+
+    fn convert_weather_units(response: WeatherResponse, to: Unit) -> WeatherResponse {
+        units::convert_weather_units(response, to)
+    }
+
+    fn required_config() -> Vec<ConfigVar> {
+        required_config()
     }
 }
 
-export!(WeatherComponent);
\ No newline at end of file
+export!(WeatherComponent with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> OpenWeatherResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn normalizes_whitespace_and_qualifiers() {
+        assert_eq!(normalize_location("  New   York ", "").unwrap(), "New York");
+        assert_eq!(normalize_location("Paris , fr", "").unwrap(), "Paris,fr");
+        assert_eq!(normalize_location("Springfield,", "").unwrap(), "Springfield");
+    }
+
+    #[test]
+    fn rejects_empty_and_overlong_locations() {
+        for location in ["", "   ", " , ,"] {
+            assert_eq!(normalize_location(location, "").unwrap_err().to_string(), "invalid_input: Location cannot be empty");
+        }
+        assert!(normalize_location("", "gb").is_err());
+
+        let long = "a".repeat(MAX_LOCATION_CHARS + 1);
+        assert_eq!(
+            normalize_location(&long, "").unwrap_err().to_string(),
+            "invalid_input: Location is too long (max 200 characters)"
+        );
+        assert!(normalize_location(&"a".repeat(MAX_LOCATION_CHARS), "").is_ok());
+    }
+
+    #[test]
+    fn appends_separate_country_code() {
+        assert_eq!(normalize_location(" London ", "gb").unwrap(), "London,GB");
+        assert_eq!(normalize_location("London", "  ").unwrap(), "London");
+    }
+
+    #[test]
+    fn rejects_invalid_country_codes() {
+        assert!(normalize_location("London", "GBR").is_err());
+        assert!(normalize_location("London", "g1").is_err());
+        assert!(normalize_location("London,UK", "GB").is_err());
+    }
+
+    #[test]
+    fn builds_place_params() {
+        let name = Place::Name { location: " Springfield ".to_string(), country: "us".to_string() };
+        assert_eq!(place_param(&name).unwrap(), "q=Springfield%2CUS");
+
+        let coords = Place::Coords { lat: 39.7817, lon: -89.6501 };
+        assert_eq!(place_param(&coords).unwrap(), "lat=39.7817&lon=-89.6501");
+    }
+
+    #[test]
+    fn rejects_out_of_range_coordinates() {
+        assert!(validate_coordinates(90.0, -180.0).is_ok());
+        assert_eq!(
+            validate_coordinates(90.5, 0.0).unwrap_err().to_string(),
+            "invalid_input: Invalid latitude 90.5: expected a value between -90 and 90"
+        );
+        assert!(validate_coordinates(0.0, 180.1).is_err());
+        assert!(validate_coordinates(f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_and_oversized_batches() {
+        let err = get_weather_multi("key", vec![], Unit::Metric).unwrap_err();
+        assert_eq!(err.to_string(), "invalid_input: At least one location is required");
+
+        let locations = vec!["Austin".to_string(); MAX_BATCH_LOCATIONS + 1];
+        let err = get_weather_multi("key", locations, Unit::Metric).unwrap_err();
+        assert_eq!(err.to_string(), "invalid_input: At most 20 locations can be checked at once, got 21");
+    }
+
+    #[test]
+    fn maps_full_response() {
+        let resp = parse(
+            r#"{
+                "name": "Austin",
+                "main": {"temp": 31.4, "feels_like": 33.0, "humidity": 48, "pressure": 1013},
+                "wind": {"speed": 4.6, "deg": 170},
+                "weather": [
+                    {"id": 800, "main": "Clear", "description": "clear sky", "icon": "01d"},
+                    {"id": 721, "main": "Haze", "description": "haze", "icon": "50d"}
+                ],
+                "dt": 1758560400,
+                "sys": {"country": "US", "sunrise": 1758544324, "sunset": 1758588190},
+                "clouds": {"all": 20},
+                "visibility": 10000
+            }"#,
+        );
+
+        let weather = map_weather(resp, Unit::Metric);
+        assert_eq!(weather.location, "Austin");
+        assert_eq!(weather.temperature, 31.4);
+        assert_eq!(weather.feels_like_temperature, 33.0);
+        assert_eq!(weather.wind_speed, Some(4.6));
+        assert_eq!(weather.wind_degrees, Some(170));
+        assert_eq!(weather.humidity, Some(48));
+        assert_eq!(weather.unit, Unit::Metric);
+        let descriptions: Vec<&str> = weather.weather_conditions.iter().map(|c| c.description.as_str()).collect();
+        assert_eq!(descriptions, ["clear sky", "haze"]);
+        assert_eq!(weather.weather_conditions[0].id, 800);
+        assert_eq!(weather.weather_conditions[0].main, "Clear");
+        assert_eq!(weather.weather_conditions[0].icon, "01d");
+        assert!(weather.comfort_index.unwrap() > weather.temperature);
+        assert_eq!(weather.comfort_label.as_deref(), Some("Hot"));
+        assert_eq!(weather.observed_at, Some(1758560400));
+        assert_eq!(weather.pressure, Some(1013));
+        assert_eq!(weather.sunrise, Some(1758544324));
+        assert_eq!(weather.sunset, Some(1758588190));
+        assert_eq!(weather.cloud_cover, Some(20));
+        assert_eq!(weather.visibility_meters, Some(10000));
+    }
+
+    #[test]
+    fn maps_missing_optional_fields_to_none() {
+        let resp = parse(
+            r#"{
+                "name": "Nowhere",
+                "main": {"temp": 12.0, "feels_like": 11.0},
+                "weather": []
+            }"#,
+        );
+
+        let weather = map_weather(resp, Unit::Metric);
+        assert_eq!(weather.wind_speed, None);
+        assert_eq!(weather.wind_degrees, None);
+        assert_eq!(weather.humidity, None);
+        assert!(weather.weather_conditions.is_empty());
+        assert_eq!(weather.comfort_index, None);
+        assert_eq!(weather.comfort_label, None);
+        assert_eq!(weather.observed_at, None);
+        assert_eq!(weather.pressure, None);
+        assert_eq!(weather.sunrise, None);
+        assert_eq!(weather.sunset, None);
+        assert_eq!(weather.cloud_cover, None);
+        assert_eq!(weather.visibility_meters, None);
+    }
+
+    #[test]
+    fn keeps_zero_wind() {
+        let resp = parse(
+            r#"{
+                "name": "Calmville",
+                "main": {"temp": 20.0, "feels_like": 20.0, "humidity": 60},
+                "wind": {"speed": 0.0, "deg": 0},
+                "weather": [{"description": "mist"}]
+            }"#,
+        );
+
+        let weather = map_weather(resp, Unit::Metric);
+        assert_eq!(weather.wind_speed, Some(0.0));
+        assert_eq!(weather.wind_degrees, Some(0));
+    }
+
+    #[test]
+    fn passes_requested_unit_through() {
+        let json = r#"{
+            "name": "Boston",
+            "main": {"temp": 68.0, "feels_like": 67.1, "humidity": 70},
+            "wind": {"speed": 9.2, "deg": 45},
+            "weather": [{"description": "few clouds"}]
+        }"#;
+
+        assert_eq!(map_weather(parse(json), Unit::Imperial).unit, Unit::Imperial);
+        assert_eq!(map_weather(parse(json), Unit::Metric).unit, Unit::Metric);
+    }
+
+    #[test]
+    fn maps_forecast_timeslots() {
+        let forecast: OpenWeatherForecastResponse = serde_json::from_str(
+            r#"{
+                "cod": "200",
+                "list": [
+                    {
+                        "dt": 1758564000,
+                        "main": {"temp": 24.1, "feels_like": 24.3, "humidity": 61},
+                        "weather": [{"id": 500, "main": "Rain", "description": "light rain", "icon": "10d"}],
+                        "wind": {"speed": 3.4, "deg": 200},
+                        "dt_txt": "2025-09-22 18:00:00"
+                    },
+                    {
+                        "dt": 1758574800,
+                        "main": {"temp": 21.0, "feels_like": 20.8},
+                        "weather": []
+                    }
+                ],
+                "city": {"name": "Austin"}
+            }"#,
+        )
+        .unwrap();
+
+        let entries: Vec<ForecastEntry> = forecast.list.into_iter().map(map_forecast_entry).collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].timestamp, 1758564000);
+        assert_eq!(entries[0].temperature, 24.1);
+        assert_eq!(entries[0].wind_speed, Some(3.4));
+        assert_eq!(entries[0].wind_degrees, Some(200));
+        assert_eq!(entries[0].humidity, Some(61));
+        assert_eq!(entries[0].weather_conditions.len(), 1);
+        assert_eq!(entries[0].weather_conditions[0].main, "Rain");
+        assert_eq!(entries[0].weather_conditions[0].description, "light rain");
+        assert_eq!(entries[0].weather_conditions[0].icon, "10d");
+        assert_eq!(entries[1].wind_speed, None);
+        assert_eq!(entries[1].humidity, None);
+        assert!(entries[1].weather_conditions.is_empty());
+    }
+
+    fn forecast_entry(timestamp: u64, temperature: f64, condition: Option<(u32, &str)>) -> ForecastEntry {
+        ForecastEntry {
+            timestamp,
+            temperature,
+            feels_like_temperature: temperature,
+            wind_speed: None,
+            wind_degrees: None,
+            humidity: None,
+            weather_conditions: condition
+                .map(|(id, main)| WeatherCondition {
+                    id,
+                    main: main.to_string(),
+                    description: main.to_lowercase(),
+                    icon: "01d".to_string(),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn summarizes_the_next_day_of_forecast() {
+        let start = 1758564000;
+        let mut entries: Vec<ForecastEntry> = (0..10)
+            .map(|slot| {
+                let condition = if slot % 3 == 0 { (500, "Rain") } else { (800, "Clear") };
+                forecast_entry(start + slot * FORECAST_SLOT_SECS, 15.0 + slot as f64, Some(condition))
+            })
+            .collect();
+        entries[4].temperature = -2.0;
+        // Past the 24-hour window, so it must not move the range.
+        entries[9].temperature = 99.0;
+
+        let summary = summarize_forecast(&entries).unwrap();
+        assert_eq!(summary.starts_at, start);
+        assert_eq!(summary.ends_at, start + OVERVIEW_WINDOW_SECS);
+        assert_eq!(summary.temperature_min, -2.0);
+        assert_eq!(summary.temperature_max, 22.0);
+        assert_eq!(summary.condition.unwrap().main, "Clear");
+    }
+
+    #[test]
+    fn breaks_condition_ties_by_first_seen() {
+        let entries = vec![
+            forecast_entry(100, 10.0, Some((600, "Snow"))),
+            forecast_entry(100 + FORECAST_SLOT_SECS, 12.0, None),
+            forecast_entry(100 + 2 * FORECAST_SLOT_SECS, 11.0, Some((800, "Clear"))),
+        ];
+
+        let summary = summarize_forecast(&entries).unwrap();
+        assert_eq!(summary.condition.unwrap().main, "Snow");
+        assert_eq!(summary.ends_at, 100 + 3 * FORECAST_SLOT_SECS);
+        assert_eq!((summary.temperature_min, summary.temperature_max), (10.0, 12.0));
+
+        let summary = summarize_forecast(&[forecast_entry(100, 10.0, None)]).unwrap();
+        assert!(summary.condition.is_none());
+        assert!(summarize_forecast(&[]).is_none());
+    }
+
+    #[test]
+    fn records_failed_overview_parts() {
+        let mut errors = Vec::new();
+        assert_eq!(overview_part(Ok(1), "Forecast", &mut errors), Some(1));
+        let failed: Result<u32, Error> = Err(PluginError::NotFound("No air quality data".to_string()).into());
+        assert_eq!(overview_part(failed, "Air quality", &mut errors), None);
+        assert_eq!(errors, vec!["not_found: Air quality: No air quality data".to_string()]);
+    }
+
+    #[test]
+    fn maps_air_quality_reading() {
+        let resp: OpenWeatherAirQualityResponse = serde_json::from_str(
+            r#"{
+                "coord": {"lon": -97.74, "lat": 30.27},
+                "list": [{
+                    "main": {"aqi": 3},
+                    "components": {"co": 230.31, "no": 0.1, "no2": 4.5, "o3": 98.71, "so2": 0.85, "pm2_5": 12.4, "pm10": 18.02, "nh3": 0.52},
+                    "dt": 1758560400
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let air = map_air_quality(resp).unwrap();
+        assert_eq!(air.aqi, 3);
+        assert_eq!(air.label, "Moderate");
+        assert_eq!(air.components.pm25, Some(12.4));
+        assert_eq!(air.components.o3, Some(98.71));
+        assert_eq!(air.measured_at, Some(1758560400));
+    }
+
+    #[test]
+    fn fails_on_empty_air_quality_list() {
+        let resp: OpenWeatherAirQualityResponse = serde_json::from_str(r#"{"list": []}"#).unwrap();
+        let err = map_air_quality(resp).unwrap_err();
+        assert_eq!(err.to_string(), "not_found: No air quality data returned for this location");
+    }
+
+    #[test]
+    fn maps_geocoding_candidates() {
+        let candidates: Vec<OpenWeatherGeoCandidate> = serde_json::from_str(
+            r#"[
+                {"name": "Springfield", "local_names": {"en": "Springfield"}, "lat": 39.7990, "lon": -89.6440, "country": "US", "state": "Illinois"},
+                {"name": "Springfield", "lat": -43.3167, "lon": 171.9333, "country": "NZ"}
+            ]"#,
+        )
+        .unwrap();
+
+        let candidates: Vec<GeoCandidate> = candidates.into_iter().map(map_geo_candidate).collect();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].name, "Springfield");
+        assert_eq!(candidates[0].country, "US");
+        assert_eq!(candidates[0].state.as_deref(), Some("Illinois"));
+        assert_eq!(candidates[0].lat, 39.7990);
+        assert_eq!(candidates[0].lon, -89.6440);
+        assert_eq!(candidates[1].state, None);
+    }
+
+    #[test]
+    fn round_trips_unit_names() {
+        for (unit, name) in [(Unit::Metric, "metric"), (Unit::Imperial, "imperial"), (Unit::Kelvin, "kelvin")] {
+            assert_eq!(serde_json::to_string(&unit).unwrap(), format!("\"{}\"", name));
+            assert_eq!(serde_json::from_str::<Unit>(&format!("\"{}\"", name)).unwrap(), unit);
+        }
+        assert_eq!(serde_json::from_str::<Unit>("\"standard\"").unwrap(), Unit::Kelvin);
+        assert!(serde_json::from_str::<Unit>("\"rankine\"").is_err());
+        assert_eq!(units_param(Unit::Kelvin), "standard");
+    }
+
+    #[test]
+    fn reports_api_key_as_required_config() {
+        let config = required_config();
+        assert_eq!(config.len(), 3);
+        assert_eq!(config[0].name, "OPENWEATHER_API_KEY");
+        assert!(config[0].required);
+        assert_eq!(config[1].name, "OPENWEATHER_TIMEOUT_SECS");
+        assert!(!config[1].required);
+        assert_eq!(config[2].name, "NOORLE_DEBUG");
+        assert!(!config[2].required);
+    }
+
+    #[test]
+    fn parses_timeout_override() {
+        assert_eq!(parse_timeout_secs(None), DEFAULT_TIMEOUT_SECS);
+        assert_eq!(parse_timeout_secs(Some(" 30 ")), 30);
+        assert_eq!(parse_timeout_secs(Some("0")), DEFAULT_TIMEOUT_SECS);
+        assert_eq!(parse_timeout_secs(Some("ten")), DEFAULT_TIMEOUT_SECS);
+    }
+}
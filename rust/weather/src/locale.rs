@@ -0,0 +1,141 @@
+use crate::error::PluginError;
+
+/// Every condition description OpenWeather returns in its default English.
+///
+/// See https://openweathermap.org/weather-conditions. Used to detect
+/// responses that fell back to English for a language it doesn't translate.
+const ENGLISH_DESCRIPTIONS: &[&str] = &[
+    "thunderstorm with light rain",
+    "thunderstorm with rain",
+    "thunderstorm with heavy rain",
+    "light thunderstorm",
+    "thunderstorm",
+    "heavy thunderstorm",
+    "ragged thunderstorm",
+    "thunderstorm with light drizzle",
+    "thunderstorm with drizzle",
+    "thunderstorm with heavy drizzle",
+    "light intensity drizzle",
+    "drizzle",
+    "heavy intensity drizzle",
+    "light intensity drizzle rain",
+    "drizzle rain",
+    "heavy intensity drizzle rain",
+    "shower rain and drizzle",
+    "heavy shower rain and drizzle",
+    "shower drizzle",
+    "light rain",
+    "moderate rain",
+    "heavy intensity rain",
+    "very heavy rain",
+    "extreme rain",
+    "freezing rain",
+    "light intensity shower rain",
+    "shower rain",
+    "heavy intensity shower rain",
+    "ragged shower rain",
+    "light snow",
+    "snow",
+    "heavy snow",
+    "sleet",
+    "light shower sleet",
+    "shower sleet",
+    "light rain and snow",
+    "rain and snow",
+    "light shower snow",
+    "shower snow",
+    "heavy shower snow",
+    "mist",
+    "smoke",
+    "haze",
+    "sand/dust whirls",
+    "fog",
+    "sand",
+    "dust",
+    "volcanic ash",
+    "squalls",
+    "tornado",
+    "clear sky",
+    "few clouds",
+    "scattered clouds",
+    "broken clouds",
+    "overcast clouds",
+];
+
+/// Normalize a requested language code ("pt_BR" -> "pt_br"); empty means none.
+///
+/// OpenWeather codes are two letters, optionally followed by `_` and a
+/// region (e.g. "fr", "zh_cn", "pt_br").
+pub fn normalize_lang(lang: &str) -> Result<Option<String>, PluginError> {
+    let lang = lang.trim().to_ascii_lowercase();
+    if lang.is_empty() {
+        return Ok(None);
+    }
+
+    let (language, region) = match lang.split_once('_') {
+        Some((language, region)) => (language, Some(region)),
+        None => (lang.as_str(), None),
+    };
+    let letters = |s: &str, len: usize| s.len() == len && s.chars().all(|c| c.is_ascii_lowercase());
+    if !letters(language, 2) || region.is_some_and(|r| !letters(r, 2)) {
+        return Err(PluginError::InvalidInput(format!(
+            "Invalid language code '{}': expected e.g. \"fr\" or \"zh_cn\"",
+            lang
+        )));
+    }
+
+    Ok(Some(lang))
+}
+
+/// Whether OpenWeather actually translated the descriptions for `lang`.
+///
+/// `None` when no translation was requested (or English was), or there are
+/// no descriptions to judge by.
+pub fn is_localized(lang: Option<&str>, descriptions: &[String]) -> Option<bool> {
+    let lang = lang?;
+    if lang == "en" || descriptions.is_empty() {
+        return None;
+    }
+
+    let all_english = descriptions
+        .iter()
+        .all(|d| ENGLISH_DESCRIPTIONS.contains(&d.trim().to_lowercase().as_str()));
+    Some(!all_english)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptions(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn normalizes_language_codes() {
+        assert_eq!(normalize_lang("").unwrap(), None);
+        assert_eq!(normalize_lang(" FR ").unwrap().as_deref(), Some("fr"));
+        assert_eq!(normalize_lang("pt_BR").unwrap().as_deref(), Some("pt_br"));
+        assert!(normalize_lang("french").is_err());
+        assert!(normalize_lang("zh-cn").is_err());
+        assert!(normalize_lang("z1").is_err());
+    }
+
+    #[test]
+    fn detects_translated_descriptions() {
+        assert_eq!(is_localized(Some("fr"), &descriptions(&["ciel dégagé"])), Some(true));
+        assert_eq!(is_localized(Some("fr"), &descriptions(&["clear sky", "brume"])), Some(true));
+    }
+
+    #[test]
+    fn detects_silent_english_fallback() {
+        assert_eq!(is_localized(Some("xh"), &descriptions(&["clear sky", "Haze"])), Some(false));
+    }
+
+    #[test]
+    fn unknown_without_a_non_english_request() {
+        assert_eq!(is_localized(None, &descriptions(&["ciel dégagé"])), None);
+        assert_eq!(is_localized(Some("en"), &descriptions(&["clear sky"])), None);
+        assert_eq!(is_localized(Some("fr"), &[]), None);
+    }
+}
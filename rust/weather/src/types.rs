@@ -9,6 +9,8 @@ impl<'de> Deserialize<'de> for crate::Unit {
         match s.as_str() {
             "metric" => Ok(crate::Unit::Metric),
             "imperial" => Ok(crate::Unit::Imperial),
+            // OpenWeather calls its Kelvin default "standard".
+            "kelvin" | "standard" => Ok(crate::Unit::Kelvin),
             _ => Err(serde::de::Error::custom(format!("unknown unit: {}", s))),
         }
     }
@@ -22,38 +24,163 @@ impl Serialize for crate::Unit {
         match self {
             crate::Unit::Metric => serializer.serialize_str("metric"),
             crate::Unit::Imperial => serializer.serialize_str("imperial"),
+            crate::Unit::Kelvin => serializer.serialize_str("kelvin"),
         }
     }
 }
 
+/// Where to look up the weather.
+#[derive(Deserialize)]
+pub enum Place {
+    /// A place name sent as `q=`, with an optional separate country code.
+    Name { location: String, country: String },
+    /// Exact coordinates sent as `lat=`/`lon=`, avoiding name ambiguity.
+    Coords { lat: f64, lon: f64 },
+}
+
 #[derive(Deserialize)]
 pub struct WeatherParams {
-    pub location: String,
+    pub place: Place,
     pub unit: crate::Unit,
+    /// OpenWeather language code for descriptions; `None` for the English default.
+    pub lang: Option<String>,
+    /// Reject observations older than this many seconds; `None` disables the check.
+    pub max_staleness_secs: Option<u64>,
+    /// Report how long the OpenWeather call took in `elapsed_ms`.
+    pub with_metrics: bool,
 }
 
 #[derive(Deserialize)]
 pub struct OpenWeatherMain {
     pub temp: f64,
     pub feels_like: f64,
-    pub humidity: usize,
+    #[serde(default)]
+    pub humidity: Option<usize>,
+    /// Sea-level atmospheric pressure in hPa.
+    #[serde(default)]
+    pub pressure: Option<f64>,
 }
 
 #[derive(Deserialize)]
 pub struct OpenWeatherWind {
-    pub speed: f64,
-    pub deg: usize,
+    #[serde(default)]
+    pub speed: Option<f64>,
+    #[serde(default)]
+    pub deg: Option<usize>,
 }
 
 #[derive(Deserialize)]
 pub struct OpenWeatherWeather {
+    /// Condition code, see https://openweathermap.org/weather-conditions.
+    #[serde(default)]
+    pub id: u32,
+    /// Condition group such as "Rain" or "Snow".
+    #[serde(default)]
+    pub main: String,
     pub description: String,
+    /// Icon code such as "10d".
+    #[serde(default)]
+    pub icon: String,
+}
+
+#[derive(Deserialize)]
+pub struct OpenWeatherClouds {
+    /// Cloud cover in percent.
+    pub all: usize,
+}
+
+#[derive(Deserialize)]
+pub struct OpenWeatherSys {
+    /// Sunrise as a Unix timestamp (seconds, UTC).
+    #[serde(default)]
+    pub sunrise: Option<u64>,
+    /// Sunset as a Unix timestamp (seconds, UTC).
+    #[serde(default)]
+    pub sunset: Option<u64>,
 }
 
 #[derive(Deserialize)]
 pub struct OpenWeatherResponse {
     pub name: String,
     pub main: OpenWeatherMain,
-    pub wind: OpenWeatherWind,
+    #[serde(default)]
+    pub wind: Option<OpenWeatherWind>,
     pub weather: Vec<OpenWeatherWeather>,
-}
\ No newline at end of file
+    /// Observation time as a Unix timestamp (seconds, UTC).
+    #[serde(default)]
+    pub dt: Option<u64>,
+    #[serde(default)]
+    pub sys: Option<OpenWeatherSys>,
+    #[serde(default)]
+    pub clouds: Option<OpenWeatherClouds>,
+    /// Visibility in meters; OpenWeather omits it for some stations and conditions.
+    #[serde(default)]
+    pub visibility: Option<usize>,
+}
+/// One 3-hour timeslot from the `/forecast` endpoint.
+#[derive(Deserialize)]
+pub struct OpenWeatherForecastEntry {
+    /// Start of the timeslot as a Unix timestamp (seconds, UTC).
+    pub dt: u64,
+    pub main: OpenWeatherMain,
+    pub weather: Vec<OpenWeatherWeather>,
+    #[serde(default)]
+    pub wind: Option<OpenWeatherWind>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenWeatherForecastResponse {
+    pub list: Vec<OpenWeatherForecastEntry>,
+}
+
+/// One match from the `/geo/1.0/direct` geocoding endpoint.
+#[derive(Deserialize)]
+pub struct OpenWeatherGeoCandidate {
+    pub name: String,
+    pub country: String,
+    #[serde(default)]
+    pub state: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+#[derive(Deserialize)]
+pub struct OpenWeatherAirQualityMain {
+    /// Air quality index from 1 (good) to 5 (very poor).
+    pub aqi: u32,
+}
+
+/// Pollutant concentrations in μg/m³.
+#[derive(Deserialize)]
+pub struct OpenWeatherAirComponents {
+    #[serde(default)]
+    pub co: Option<f64>,
+    #[serde(default)]
+    pub no: Option<f64>,
+    #[serde(default)]
+    pub no2: Option<f64>,
+    #[serde(default)]
+    pub o3: Option<f64>,
+    #[serde(default)]
+    pub so2: Option<f64>,
+    #[serde(default)]
+    pub pm2_5: Option<f64>,
+    #[serde(default)]
+    pub pm10: Option<f64>,
+    #[serde(default)]
+    pub nh3: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenWeatherAirQualityReading {
+    pub main: OpenWeatherAirQualityMain,
+    pub components: OpenWeatherAirComponents,
+    /// Measurement time as a Unix timestamp (seconds, UTC).
+    #[serde(default)]
+    pub dt: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenWeatherAirQualityResponse {
+    pub list: Vec<OpenWeatherAirQualityReading>,
+}
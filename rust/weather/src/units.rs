@@ -0,0 +1,187 @@
+use crate::{comfort, Unit, WeatherResponse};
+
+/// Meters per second in one mile per hour.
+const MPS_PER_MPH: f64 = 0.44704;
+
+/// Re-express a response in another unit system without a second request.
+///
+/// Temperature, feels-like and comfort index move between °C, °F and K; wind
+/// speed between m/s (`Metric` and `Kelvin`) and mph (`Imperial`). The comfort
+/// label is recomputed for the converted index, and everything else is copied
+/// as is. Converting to the response's own unit returns it unchanged.
+pub fn convert_weather_units(response: WeatherResponse, to: Unit) -> WeatherResponse {
+    let from = response.unit;
+    if from == to {
+        return response;
+    }
+
+    let comfort_index = response
+        .comfort_index
+        .map(|index| convert_temperature(index, from, to));
+    WeatherResponse {
+        temperature: convert_temperature(response.temperature, from, to),
+        feels_like_temperature: convert_temperature(response.feels_like_temperature, from, to),
+        wind_speed: response.wind_speed.map(|speed| convert_wind_speed(speed, from, to)),
+        unit: to,
+        comfort_index,
+        comfort_label: response
+            .comfort_label
+            .and(comfort_index)
+            .map(|index| comfort::comfort_label(index, to).to_string()),
+        ..response
+    }
+}
+
+/// Convert a temperature between the unit systems' scales.
+pub fn convert_temperature(value: f64, from: Unit, to: Unit) -> f64 {
+    let celsius = match from {
+        Unit::Metric => value,
+        Unit::Imperial => fahrenheit_to_celsius(value),
+        Unit::Kelvin => kelvin_to_celsius(value),
+    };
+    match to {
+        Unit::Metric => celsius,
+        Unit::Imperial => celsius_to_fahrenheit(celsius),
+        Unit::Kelvin => celsius_to_kelvin(celsius),
+    }
+}
+
+/// Convert a wind speed between m/s and mph. `Kelvin` responses use m/s like
+/// `Metric`.
+pub fn convert_wind_speed(value: f64, from: Unit, to: Unit) -> f64 {
+    let mps = match from {
+        Unit::Imperial => value * MPS_PER_MPH,
+        Unit::Metric | Unit::Kelvin => value,
+    };
+    match to {
+        Unit::Imperial => mps / MPS_PER_MPH,
+        Unit::Metric | Unit::Kelvin => mps,
+    }
+}
+
+pub fn celsius_to_fahrenheit(c: f64) -> f64 {
+    c * 9.0 / 5.0 + 32.0
+}
+
+pub fn fahrenheit_to_celsius(f: f64) -> f64 {
+    (f - 32.0) * 5.0 / 9.0
+}
+
+pub fn celsius_to_kelvin(c: f64) -> f64 {
+    c + 273.15
+}
+
+pub fn kelvin_to_celsius(k: f64) -> f64 {
+    k - 273.15
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric_response() -> WeatherResponse {
+        WeatherResponse {
+            location: "Austin".to_string(),
+            temperature: 20.0,
+            feels_like_temperature: 18.5,
+            wind_speed: Some(4.4704),
+            wind_degrees: Some(180),
+            humidity: Some(50),
+            pressure: Some(1013),
+            cloud_cover: Some(40),
+            visibility_meters: Some(10000),
+            unit: Unit::Metric,
+            weather_conditions: Vec::new(),
+            comfort_index: Some(20.0),
+            comfort_label: Some("Comfortable".to_string()),
+            observed_at: Some(1_700_000_000),
+            sunrise: Some(1_699_970_000),
+            sunset: Some(1_700_010_000),
+            localized: None,
+            elapsed_ms: Some(120),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn converts_metric_to_imperial() {
+        let imperial = convert_weather_units(metric_response(), Unit::Imperial);
+
+        assert_eq!(imperial.unit, Unit::Imperial);
+        assert_close(imperial.temperature, 68.0);
+        assert_close(imperial.feels_like_temperature, 65.3);
+        assert_close(imperial.wind_speed.unwrap(), 10.0);
+        assert_close(imperial.comfort_index.unwrap(), 68.0);
+        assert_eq!(imperial.comfort_label.as_deref(), Some("Comfortable"));
+        assert_eq!(imperial.humidity, Some(50));
+        assert_eq!(imperial.visibility_meters, Some(10000));
+        assert_eq!(imperial.elapsed_ms, Some(120));
+    }
+
+    #[test]
+    fn converts_to_kelvin_keeping_wind_in_meters_per_second() {
+        let kelvin = convert_weather_units(metric_response(), Unit::Kelvin);
+
+        assert_eq!(kelvin.unit, Unit::Kelvin);
+        assert_close(kelvin.temperature, 293.15);
+        assert_close(kelvin.wind_speed.unwrap(), 4.4704);
+
+        let imperial = convert_weather_units(kelvin, Unit::Imperial);
+        assert_close(imperial.temperature, 68.0);
+        assert_close(imperial.wind_speed.unwrap(), 10.0);
+    }
+
+    #[test]
+    fn round_trips_back_to_the_original_values() {
+        for via in [Unit::Imperial, Unit::Kelvin] {
+            let original = metric_response();
+            let back = convert_weather_units(convert_weather_units(metric_response(), via), Unit::Metric);
+
+            assert_eq!(back.unit, Unit::Metric);
+            assert_close(back.temperature, original.temperature);
+            assert_close(back.feels_like_temperature, original.feels_like_temperature);
+            assert_close(back.wind_speed.unwrap(), original.wind_speed.unwrap());
+            assert_close(back.comfort_index.unwrap(), original.comfort_index.unwrap());
+            assert_eq!(back.comfort_label, original.comfort_label);
+        }
+    }
+
+    #[test]
+    fn converting_to_the_same_unit_is_a_no_op() {
+        let original = metric_response();
+        let same = convert_weather_units(metric_response(), Unit::Metric);
+
+        assert_eq!(same.unit, Unit::Metric);
+        assert_eq!(same.temperature, original.temperature);
+        assert_eq!(same.feels_like_temperature, original.feels_like_temperature);
+        assert_eq!(same.wind_speed, original.wind_speed);
+        assert_eq!(same.comfort_index, original.comfort_index);
+    }
+
+    #[test]
+    fn leaves_missing_readings_missing() {
+        let response = WeatherResponse {
+            wind_speed: None,
+            comfort_index: None,
+            comfort_label: None,
+            ..metric_response()
+        };
+        let imperial = convert_weather_units(response, Unit::Imperial);
+
+        assert_eq!(imperial.wind_speed, None);
+        assert_eq!(imperial.comfort_index, None);
+        assert_eq!(imperial.comfort_label, None);
+    }
+
+    #[test]
+    fn converts_temperatures_between_every_scale() {
+        assert_close(convert_temperature(-40.0, Unit::Metric, Unit::Imperial), -40.0);
+        assert_close(convert_temperature(212.0, Unit::Imperial, Unit::Metric), 100.0);
+        assert_close(convert_temperature(273.15, Unit::Kelvin, Unit::Imperial), 32.0);
+        assert_close(convert_temperature(32.0, Unit::Imperial, Unit::Kelvin), 273.15);
+        assert_close(convert_wind_speed(1.0, Unit::Kelvin, Unit::Metric), 1.0);
+    }
+}